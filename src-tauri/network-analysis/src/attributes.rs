@@ -0,0 +1,573 @@
+use crate::dataset::Dataset;
+use crate::error::Error;
+use crate::formats::delimited::Column;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A single attribute value, as sent to and received from the frontend.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeKind {
+    Integer,
+    Float,
+    Text,
+}
+
+/// Values of one attribute, indexed by node index. Nodes missing from the
+/// attribute file have no value.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ColumnValues {
+    Integer(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttributeColumn {
+    pub name: String,
+    pub values: ColumnValues,
+}
+
+impl AttributeColumn {
+    pub fn kind(&self) -> AttributeKind {
+        match self.values {
+            ColumnValues::Integer(_) => AttributeKind::Integer,
+            ColumnValues::Float(_) => AttributeKind::Float,
+            ColumnValues::Text(_) => AttributeKind::Text,
+        }
+    }
+
+    pub fn value(&self, node: usize) -> Option<AttributeValue> {
+        match &self.values {
+            ColumnValues::Integer(values) => values[node].map(AttributeValue::Integer),
+            ColumnValues::Float(values) => values[node].map(AttributeValue::Float),
+            ColumnValues::Text(values) => values[node].clone().map(AttributeValue::Text),
+        }
+    }
+
+    /// Nodes grouped by their value, in ascending order of value.
+    /// Nodes without a value are left out.
+    pub fn groups(&self) -> Vec<(AttributeValue, Vec<usize>)> {
+        match &self.values {
+            ColumnValues::Integer(values) => group(values, |&v| v, AttributeValue::Integer),
+            // grouped by bit pattern, so every distinct float gets its own group
+            ColumnValues::Float(values) => {
+                let mut groups = group(
+                    values,
+                    |&v| ordered_bits(v),
+                    |bits| AttributeValue::Float(f64::from_bits(unordered_bits(bits))),
+                );
+                groups
+                    .retain(|(value, _)| !matches!(value, AttributeValue::Float(v) if v.is_nan()));
+                groups
+            }
+            ColumnValues::Text(values) => group(values, |v| v.clone(), AttributeValue::Text),
+        }
+    }
+}
+
+fn group<T, K: Ord>(
+    values: &[Option<T>],
+    key: impl Fn(&T) -> K,
+    value: impl Fn(K) -> AttributeValue,
+) -> Vec<(AttributeValue, Vec<usize>)> {
+    let mut groups: BTreeMap<K, Vec<usize>> = BTreeMap::new();
+    for (node, v) in values.iter().enumerate() {
+        if let Some(v) = v {
+            groups.entry(key(v)).or_default().push(node);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(key, nodes)| (value(key), nodes))
+        .collect()
+}
+
+/// Maps a float to an integer with the same order.
+fn ordered_bits(value: f64) -> u64 {
+    let bits = value.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+fn unordered_bits(bits: u64) -> u64 {
+    if bits >> 63 == 1 {
+        bits & !(1 << 63)
+    } else {
+        !bits
+    }
+}
+
+/// The attribute columns of a dataset.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NodeAttributes {
+    pub columns: Vec<AttributeColumn>,
+}
+
+impl NodeAttributes {
+    pub fn column(&self, name: &str) -> Result<&AttributeColumn, Error> {
+        self.columns
+            .iter()
+            .find(|column| column.name == name)
+            .ok_or_else(|| Error::UnknownAttribute {
+                name: name.to_string(),
+            })
+    }
+
+    /// Adds `column`, replacing a column with the same name.
+    fn insert(&mut self, column: AttributeColumn) {
+        match self.columns.iter_mut().find(|c| c.name == column.name) {
+            Some(existing) => *existing = column,
+            None => self.columns.push(column),
+        }
+    }
+
+    /// The attributes of another dataset whose node `i` is node `nodes[i]`
+    /// here, or has no values when `nodes[i]` is `None`.
+    pub fn select(&self, nodes: &[Option<usize>]) -> NodeAttributes {
+        fn pick<T: Clone>(values: &[Option<T>], nodes: &[Option<usize>]) -> Vec<Option<T>> {
+            nodes
+                .iter()
+                .map(|&node| node.and_then(|node| values[node].clone()))
+                .collect()
+        }
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| AttributeColumn {
+                name: column.name.clone(),
+                values: match &column.values {
+                    ColumnValues::Integer(values) => ColumnValues::Integer(pick(values, nodes)),
+                    ColumnValues::Float(values) => ColumnValues::Float(pick(values, nodes)),
+                    ColumnValues::Text(values) => ColumnValues::Text(pick(values, nodes)),
+                },
+            })
+            .collect();
+        NodeAttributes { columns }
+    }
+
+    pub fn info(&self) -> Vec<ColumnInfo> {
+        self.columns
+            .iter()
+            .map(|column| ColumnInfo {
+                name: column.name.clone(),
+                kind: column.kind(),
+                distinct_values: column.groups().len(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub kind: AttributeKind,
+    pub distinct_values: usize,
+}
+
+/// How the key column of an attribute file refers to nodes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchBy {
+    /// The node ID from the graph file.
+    #[default]
+    Id,
+    /// The node label, or the original identifier for CSV edge lists.
+    Label,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributeSummary {
+    pub columns: Vec<ColumnInfo>,
+    /// Rows whose key matched a node.
+    pub matched: usize,
+    /// Rows whose key did not match any node.
+    pub unmatched: usize,
+}
+
+/// Reads a CSV file with a header row and one row per node, and returns a
+/// copy of `dataset` with every column except the key column added to its
+/// node attributes. The graph is unchanged, so the copy keeps the metrics
+/// cached so far.
+/// A column is typed as integer or float when all its values parse as such,
+/// and as text otherwise.
+pub fn load(
+    dataset: &Dataset,
+    path: &Path,
+    key_column: &Column,
+    match_by: MatchBy,
+    delimiter: char,
+) -> Result<(Dataset, AttributeSummary), Error> {
+    if !delimiter.is_ascii() {
+        return Err(Error::InvalidFormat {
+            message: format!("delimiter {:?} is not an ASCII character", delimiter),
+        });
+    }
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_path(path)
+        .map_err(invalid)?;
+    let headers = reader.headers().map_err(invalid)?.clone();
+    let key = match key_column {
+        Column::Index(index) => *index,
+        Column::Name(name) => {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| Error::InvalidFormat {
+                    message: format!("no column named {:?}", name),
+                })?
+        }
+    };
+
+    let by_label = match match_by {
+        MatchBy::Id => Default::default(),
+        MatchBy::Label => dataset.labels.lookup(),
+    };
+    let node_count = dataset.node_count();
+    let mut raw: Vec<Vec<Option<String>>> = vec![vec![None; node_count]; headers.len()];
+    let (mut matched, mut unmatched) = (0, 0);
+    for record in reader.records() {
+        let record = record.map_err(invalid)?;
+        let node = record.get(key).and_then(|key| match match_by {
+            MatchBy::Id => dataset.index(key.parse().ok()?),
+            MatchBy::Label => dataset.index(*by_label.get(key)?),
+        });
+        let node = match node {
+            Some(node) => node,
+            None => {
+                unmatched += 1;
+                continue;
+            }
+        };
+        matched += 1;
+        for (column, value) in record.iter().enumerate().take(headers.len()) {
+            if column != key && !value.is_empty() {
+                raw[column][node] = Some(value.to_string());
+            }
+        }
+    }
+
+    let mut loaded = dataset.clone();
+    for (column, values) in raw.into_iter().enumerate() {
+        if column == key {
+            continue;
+        }
+        loaded.attributes.insert(AttributeColumn {
+            name: headers[column].to_string(),
+            values: typed(values),
+        });
+    }
+    let summary = AttributeSummary {
+        columns: loaded.attributes.info(),
+        matched,
+        unmatched,
+    };
+    Ok((loaded, summary))
+}
+
+fn typed(values: Vec<Option<String>>) -> ColumnValues {
+    let present = || values.iter().flatten();
+    if present().all(|v| v.parse::<i64>().is_ok()) {
+        ColumnValues::Integer(
+            values
+                .iter()
+                .map(|v| v.as_ref().map(|v| v.parse().unwrap()))
+                .collect(),
+        )
+    } else if present().all(|v| v.parse::<f64>().is_ok()) {
+        ColumnValues::Float(
+            values
+                .iter()
+                .map(|v| v.as_ref().map(|v| v.parse().unwrap()))
+                .collect(),
+        )
+    } else {
+        ColumnValues::Text(values)
+    }
+}
+
+/// A test on the value of one attribute column.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AttributeFilter {
+    pub column: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Condition {
+    Equals {
+        value: AttributeValue,
+    },
+    OneOf {
+        values: Vec<AttributeValue>,
+    },
+    /// Numeric columns only. Both bounds are inclusive.
+    Range {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+}
+
+impl AttributeFilter {
+    /// The node indices whose value passes the filter.
+    pub fn matching_nodes(&self, attributes: &NodeAttributes) -> Result<Vec<usize>, Error> {
+        let column = attributes.column(&self.column)?;
+        let length = match &column.values {
+            ColumnValues::Integer(values) => values.len(),
+            ColumnValues::Float(values) => values.len(),
+            ColumnValues::Text(values) => values.len(),
+        };
+        Ok((0..length)
+            .filter(|&node| match column.value(node) {
+                Some(value) => self.condition.accepts(&value),
+                None => false,
+            })
+            .collect())
+    }
+}
+
+impl Condition {
+    fn accepts(&self, value: &AttributeValue) -> bool {
+        match self {
+            Condition::Equals { value: expected } => same(value, expected),
+            Condition::OneOf { values } => values.iter().any(|expected| same(value, expected)),
+            Condition::Range { min, max } => match number(value) {
+                Some(v) => min.map_or(true, |min| v >= min) && max.map_or(true, |max| v <= max),
+                None => false,
+            },
+        }
+    }
+}
+
+fn number(value: &AttributeValue) -> Option<f64> {
+    match value {
+        AttributeValue::Integer(v) => Some(*v as f64),
+        AttributeValue::Float(v) => Some(*v),
+        AttributeValue::Text(_) => None,
+    }
+}
+
+/// Compares across numeric kinds, so `3` from the frontend matches `3.0`.
+fn same(value: &AttributeValue, expected: &AttributeValue) -> bool {
+    match (number(value), number(expected)) {
+        (Some(a), Some(b)) => a == b,
+        _ => value == expected,
+    }
+}
+
+const PALETTE: [&str; 12] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf", "#aec7e8", "#ffbb78",
+];
+
+/// A colour per node index for the given column. Integer and text values get
+/// a colour from a categorical palette, float values a blue to red gradient.
+/// Nodes without a value are left out.
+pub fn colours(column: &AttributeColumn) -> Vec<(usize, String)> {
+    let mut colours = Vec::new();
+    match &column.values {
+        ColumnValues::Float(values) => {
+            let present = values.iter().flatten().filter(|v| v.is_finite());
+            let min = present.clone().fold(f64::INFINITY, |a, &b| a.min(b));
+            let max = present.fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+            for (node, value) in values.iter().enumerate() {
+                if let Some(value) = value.filter(|v| v.is_finite()) {
+                    let t = if max > min {
+                        (value - min) / (max - min)
+                    } else {
+                        0.5
+                    };
+                    let red = (t * 255.0).round() as u8;
+                    let blue = 255 - red;
+                    colours.push((node, format!("#{:02x}40{:02x}", red, blue)));
+                }
+            }
+        }
+        _ => {
+            for (index, (_, nodes)) in column.groups().into_iter().enumerate() {
+                let colour = PALETTE[index % PALETTE.len()];
+                colours.extend(nodes.into_iter().map(|node| (node, colour.to_string())));
+            }
+        }
+    }
+    colours
+}
+
+fn invalid(error: ::csv::Error) -> Error {
+    Error::InvalidFormat {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{DatasetBuilder, GraphMode};
+    use crate::loader::DuplicatePolicy;
+
+    /// The path 1-2-3 and node 4, with node 2 labelled "bob".
+    fn dataset() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        builder.insert_edge(1, 2, 1.0);
+        builder.insert_edge(2, 3, 1.0);
+        builder.insert_node(4);
+        builder.labels.set_label(2, "bob".to_string());
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    fn import(
+        dataset: &mut Dataset,
+        name: &str,
+        content: &str,
+        key: Column,
+        match_by: MatchBy,
+    ) -> Result<AttributeSummary, Error> {
+        let path = crate::temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        let loaded = load(dataset, &path, &key, match_by, ',');
+        std::fs::remove_file(&path).unwrap();
+        let (loaded, summary) = loaded?;
+        *dataset = loaded;
+        Ok(summary)
+    }
+
+    #[test]
+    fn typed_columns() {
+        let mut dataset = dataset();
+        let summary = import(
+            &mut dataset,
+            "attributes.csv",
+            "id, age, score, team\n1, 30, 0.5, red\n3, 41, 2, blue\n9, 1, 1, red\n4, , 1e3, red\n",
+            Column::Name("id".to_string()),
+            MatchBy::Id,
+        )
+        .unwrap();
+        assert_eq!((summary.matched, summary.unmatched), (3, 1));
+        let kinds: Vec<_> = summary
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("age", AttributeKind::Integer),
+                ("score", AttributeKind::Float),
+                ("team", AttributeKind::Text)
+            ]
+        );
+
+        let attributes = &dataset.attributes;
+        let age = attributes.column("age").unwrap();
+        assert_eq!(age.value(0), Some(AttributeValue::Integer(30)));
+        assert_eq!(age.value(1), None);
+        assert_eq!(age.value(3), None);
+        let team = attributes.column("team").unwrap();
+        assert_eq!(team.groups().len(), 2);
+        assert_eq!(colours(team).len(), 3);
+        assert!(matches!(
+            attributes.column("height"),
+            Err(Error::UnknownAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn match_by_label() {
+        let mut dataset = dataset();
+        let summary = import(
+            &mut dataset,
+            "labels.csv",
+            "name,role\nbob,admin\nalice,user\n",
+            Column::Index(0),
+            MatchBy::Label,
+        )
+        .unwrap();
+        assert_eq!((summary.matched, summary.unmatched), (1, 1));
+        let role = dataset.attributes.column("role").unwrap();
+        assert_eq!(
+            role.value(1),
+            Some(AttributeValue::Text("admin".to_string()))
+        );
+
+        // a second file replaces the column of the same name
+        import(
+            &mut dataset,
+            "labels-again.csv",
+            "name,role\nbob,user\n",
+            Column::Index(0),
+            MatchBy::Label,
+        )
+        .unwrap();
+        assert_eq!(dataset.attributes.columns.len(), 1);
+        let role = dataset.attributes.column("role").unwrap();
+        assert_eq!(
+            role.value(1),
+            Some(AttributeValue::Text("user".to_string()))
+        );
+    }
+
+    #[test]
+    fn missing_key_column() {
+        let mut dataset = dataset();
+        let result = import(
+            &mut dataset,
+            "no-key.csv",
+            "node,age\n1,30\n",
+            Column::Name("id".to_string()),
+            MatchBy::Id,
+        );
+        assert!(matches!(result, Err(Error::InvalidFormat { .. })));
+        assert!(dataset.attributes.columns.is_empty());
+    }
+
+    #[test]
+    fn filters() {
+        let mut dataset = dataset();
+        import(
+            &mut dataset,
+            "filters.csv",
+            "id,age\n1,30\n2,41\n3,52\n",
+            Column::Index(0),
+            MatchBy::Id,
+        )
+        .unwrap();
+        let filter = |condition| AttributeFilter {
+            column: "age".to_string(),
+            condition,
+        };
+        let range = filter(Condition::Range {
+            min: Some(35.0),
+            max: None,
+        });
+        assert_eq!(
+            range.matching_nodes(&dataset.attributes).unwrap(),
+            vec![1, 2]
+        );
+        let equals = filter(Condition::Equals {
+            value: AttributeValue::Float(30.0),
+        });
+        assert_eq!(equals.matching_nodes(&dataset.attributes).unwrap(), vec![0]);
+
+        let selected = dataset.attributes.select(&[Some(2), None]);
+        let age = selected.column("age").unwrap();
+        assert_eq!(age.value(0), Some(AttributeValue::Integer(52)));
+        assert_eq!(age.value(1), None);
+    }
+}
@@ -0,0 +1,406 @@
+//! Headless front end: loads a dataset, runs the requested analyses and
+//! writes the results as JSON or CSV, without starting the desktop app.
+//!
+//! Progress and timing messages go to stderr so stdout only holds results.
+
+use network_analysis::dataset::{Dataset, DegreeMode};
+use network_analysis::error::Error;
+use network_analysis::{functions, influence, loader, path, snapshot};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+const USAGE: &str = "usage: network-cli <path> [options]
+
+Input:
+  --snapshot               read <path> as a binary snapshot
+  --format <format>        edge_list, csv, graph_ml, gml or pajek (default: detected)
+  --directed               treat edges as directed
+  --undirected             treat edges as undirected
+  --weight-column <n>      column of the edge weight in edge lists
+  --timestamp-column <n>   column of the edge timestamp in edge lists
+  --duplicates <policy>    first, sum or max (default: first)
+  --drop-self-loops        drop edges from a node to itself
+  --strict                 fail on the first malformed line
+
+Analyses:
+  --analysis <a,b,...>     analyses to run (default: summary)
+  --direction <mode>       out, in or total degrees for directed graphs
+  --bins <n>               bins of the clustering distribution (default: 10)
+  --start <id> --end <id>  endpoints of shortest_path
+  --seeds <id,id,...>      first influenced nodes of influence_spread
+  --steps <n>              steps of influence_spread (default: 500)
+  --probability <p>        spread probability of influence_spread (default: 0.5)
+  --best <n>               nodes returned by best_starting_nodes (default: 5)
+  --labels                 report nodes by label instead of ID
+
+Output:
+  --output <file>          write results to <file> instead of stdout
+  --output-format <fmt>    json or csv (default: json)
+
+Analyses: summary, node_count, edge_count, average_degree, max_degree,
+degree_distribution, clustering_effect, average_clustering, transitivity,
+clustering, clustering_distribution, clustering_effect_distribution,
+average_common_neighbours, max_common_neighbours,
+max_common_neighbour_pair, communities,
+shortest_path, influence_spread, best_starting_nodes";
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Analysis {
+    Summary,
+    NodeCount,
+    EdgeCount,
+    AverageDegree,
+    MaxDegree,
+    DegreeDistribution,
+    ClusteringEffect,
+    AverageClustering,
+    Transitivity,
+    Clustering,
+    ClusteringDistribution,
+    ClusteringEffectDistribution,
+    AverageCommonNeighbours,
+    MaxCommonNeighbours,
+    MaxCommonNeighbourPair,
+    Communities,
+    ShortestPath,
+    InfluenceSpread,
+    BestStartingNodes,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum OutputFormat {
+    Json,
+    Csv,
+}
+
+struct Args {
+    path: PathBuf,
+    snapshot: bool,
+    options: loader::LoadOptions,
+    analyses: Vec<Analysis>,
+    direction: DegreeMode,
+    bins: u32,
+    start: Option<usize>,
+    end: Option<usize>,
+    seeds: Vec<usize>,
+    steps: u32,
+    probability: f64,
+    best: u32,
+    labels: bool,
+    output: Option<PathBuf>,
+    output_format: OutputFormat,
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidArgument { message }
+}
+
+/// Parses a snake_case enum value the same way the Tauri commands receive it.
+fn parse_enum<T: for<'de> Deserialize<'de>>(flag: &str, value: &str) -> Result<T, Error> {
+    serde_json::from_value(Value::String(value.to_string()))
+        .map_err(|_| invalid(format!("unknown value {:?} for {}", value, flag)))
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("{} expects a number, got {:?}", flag, value)))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, Error> {
+    let mut path = None;
+    let mut parsed = Args {
+        path: PathBuf::new(),
+        snapshot: false,
+        options: loader::LoadOptions::default(),
+        analyses: vec![Analysis::Summary],
+        direction: DegreeMode::default(),
+        bins: 10,
+        start: None,
+        end: None,
+        seeds: Vec::new(),
+        steps: 500,
+        probability: 0.5,
+        best: 5,
+        labels: false,
+        output: None,
+        output_format: OutputFormat::Json,
+    };
+    while let Some(arg) = args.next() {
+        let flag = arg.as_str();
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| invalid(format!("{} expects a value", flag)))
+        };
+        match flag {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "--snapshot" => parsed.snapshot = true,
+            "--format" => parsed.options.format = Some(parse_enum(flag, &value()?)?),
+            "--directed" => parsed.options.directed = Some(true),
+            "--undirected" => parsed.options.directed = Some(false),
+            "--weight-column" => {
+                parsed.options.weight_column = Some(parse_number(flag, &value()?)?)
+            }
+            "--timestamp-column" => {
+                parsed.options.timestamp_column = Some(parse_number(flag, &value()?)?)
+            }
+            "--duplicates" => parsed.options.duplicates = parse_enum(flag, &value()?)?,
+            "--drop-self-loops" => parsed.options.self_loops = loader::SelfLoopPolicy::Drop,
+            "--strict" => parsed.options.strict = true,
+            "--analysis" => {
+                parsed.analyses = value()?
+                    .split(',')
+                    .map(|name| parse_enum(flag, name.trim()))
+                    .collect::<Result<_, _>>()?
+            }
+            "--direction" => parsed.direction = parse_enum(flag, &value()?)?,
+            "--bins" => parsed.bins = parse_number(flag, &value()?)?,
+            "--start" => parsed.start = Some(parse_number(flag, &value()?)?),
+            "--end" => parsed.end = Some(parse_number(flag, &value()?)?),
+            "--seeds" => {
+                parsed.seeds = value()?
+                    .split(',')
+                    .map(|node| parse_number(flag, node.trim()))
+                    .collect::<Result<_, _>>()?
+            }
+            "--steps" => parsed.steps = parse_number(flag, &value()?)?,
+            "--probability" => parsed.probability = parse_number(flag, &value()?)?,
+            "--best" => parsed.best = parse_number(flag, &value()?)?,
+            "--labels" => parsed.labels = true,
+            "--output" => parsed.output = Some(PathBuf::from(value()?)),
+            "--output-format" => parsed.output_format = parse_enum(flag, &value()?)?,
+            _ if flag.starts_with("--") => return Err(invalid(format!("unknown option {}", flag))),
+            _ if path.is_none() => path = Some(PathBuf::from(flag)),
+            _ => return Err(invalid(format!("unexpected argument {:?}", flag))),
+        }
+    }
+    parsed.path = path.ok_or_else(|| invalid("missing input path".to_string()))?;
+    Ok(parsed)
+}
+
+fn load(args: &Args) -> Result<Dataset, Error> {
+    eprintln!("Loading {}", args.path.display());
+    if args.snapshot {
+        return snapshot::load(&args.path);
+    }
+    let cancel = AtomicBool::new(false);
+    let report = |progress: &loader::LoadProgress| {
+        eprint!("\r{} / {} bytes", progress.bytes_read, progress.total_bytes);
+    };
+    let total_bytes = std::fs::metadata(&args.path)?.len();
+    let mut monitor = loader::LoadMonitor::new(total_bytes, &cancel, &report);
+    let dataset = loader::load_file(&args.path, &args.options, &mut monitor)?;
+    eprintln!();
+    if let Some(report) = &dataset.report {
+        if report.lines_skipped > 0 {
+            eprintln!("Skipped {} malformed lines", report.lines_skipped);
+        }
+    }
+    Ok(dataset)
+}
+
+fn run(dataset: &Dataset, analysis: Analysis, args: &Args) -> Result<Value, Error> {
+    let view = dataset.view(args.direction);
+    let graph = &*view;
+    let key = |node: usize| dataset.labels.key(dataset.id(node), args.labels);
+    // computed once and shared by the clustering analyses
+    let metrics = || dataset.node_metrics(args.direction);
+    let value = match analysis {
+        Analysis::Summary => json!({
+            "mode": dataset.mode,
+            "node_count": dataset.node_count(),
+            "edge_count": dataset.edge_count(),
+            "isolated_nodes": dataset.isolated_node_count(),
+            "time_range": dataset.timeline.as_ref().and_then(|timeline| timeline.range()),
+        }),
+        Analysis::NodeCount => json!(functions::get_node_count(&dataset.graph)),
+        Analysis::EdgeCount => json!(dataset.edge_count()),
+        Analysis::AverageDegree => json!(functions::get_avg_dg(graph)?),
+        Analysis::MaxDegree => json!(functions::get_max_dg(graph)?),
+        Analysis::DegreeDistribution => json!(functions::get_dg_dis(graph)),
+        Analysis::ClusteringEffect => json!(functions::get_cl_ef_of(&metrics().triangles)?),
+        Analysis::AverageClustering => {
+            json!(functions::get_avg_cl_coef_of(&metrics().clustering)?)
+        }
+        Analysis::Transitivity => {
+            let metrics = metrics();
+            json!(functions::get_transitivity_of(
+                &metrics.degrees,
+                &metrics.triangles
+            )?)
+        }
+        Analysis::Clustering => Value::Array(
+            metrics()
+                .clustering
+                .iter()
+                .enumerate()
+                .map(|(node, coefficient)| json!([key(node), coefficient]))
+                .collect(),
+        ),
+        Analysis::ClusteringDistribution => {
+            json!(functions::get_cl_coef_dis_of(
+                &metrics().clustering,
+                args.bins
+            )?)
+        }
+        Analysis::ClusteringEffectDistribution => {
+            let metrics = metrics();
+            json!(functions::get_cl_ef_dis_of(
+                &metrics.degrees,
+                &metrics.clustering
+            ))
+        }
+        Analysis::AverageCommonNeighbours => json!(functions::get_avg_cm_nb(graph)?),
+        Analysis::MaxCommonNeighbours => json!(functions::get_max_cm_ng(graph)),
+        Analysis::MaxCommonNeighbourPair => match functions::get_max_cm_pair(graph) {
+            Some((first, second, common)) => json!([key(first), key(second), common]),
+            None => Value::Null,
+        },
+        Analysis::Communities => Value::Array(
+            dataset
+                .communities()
+                .iter()
+                .enumerate()
+                .map(|(node, &community)| json!([key(node), dataset.id(community)]))
+                .collect(),
+        ),
+        Analysis::ShortestPath => {
+            let (start, end) = match (args.start, args.end) {
+                (Some(start), Some(end)) => (start, end),
+                _ => return Err(invalid("shortest_path needs --start and --end".to_string())),
+            };
+            let start = dataset.ensure_node(start)?;
+            let end = dataset.ensure_node(end)?;
+            match path::dijkstra(&dataset.graph, start, end) {
+                Some(path) => json!(path.into_iter().map(key).collect::<Vec<_>>()),
+                None => Value::Null,
+            }
+        }
+        Analysis::InfluenceSpread => {
+            if args.seeds.is_empty() {
+                return Err(invalid("influence_spread needs --seeds".to_string()));
+            }
+            let seeds = args
+                .seeds
+                .iter()
+                .map(|&node| dataset.ensure_node(node))
+                .collect::<Result<Vec<_>, _>>()?;
+            let history = influence::simulate_influnce_spread(
+                &dataset.graph,
+                seeds,
+                args.steps,
+                args.probability,
+            );
+            let mut influenced: Vec<usize> = history.into_iter().flatten().collect();
+            influenced.sort_unstable();
+            influenced.dedup();
+            json!(influenced.into_iter().map(key).collect::<Vec<_>>())
+        }
+        Analysis::BestStartingNodes => json!(influence::get_best_starting_nodes(
+            &dataset.graph,
+            args.best
+        )
+        .into_iter()
+        .map(key)
+        .collect::<Vec<_>>()),
+    };
+    Ok(value)
+}
+
+fn analysis_name(analysis: Analysis) -> String {
+    match serde_json::to_value(analysis) {
+        Ok(Value::String(name)) => name,
+        _ => format!("{:?}", analysis),
+    }
+}
+
+fn write_json<W: Write>(out: &mut W, results: &Map<String, Value>) -> Result<(), Error> {
+    serde_json::to_writer_pretty(&mut *out, results).map_err(|error| Error::Io {
+        message: error.to_string(),
+    })?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// One `analysis,key,value` row per value. Scalars have an empty key, pairs
+/// such as distributions are split into key and value, and lists are keyed
+/// by position.
+fn write_csv<W: Write>(out: W, results: &Map<String, Value>) -> Result<(), Error> {
+    let mut writer = ::csv::Writer::from_writer(out);
+    let csv_error = |error: ::csv::Error| Error::Io {
+        message: error.to_string(),
+    };
+    let cell = |value: &Value| match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    writer
+        .write_record(["analysis", "key", "value"])
+        .map_err(csv_error)?;
+    for (name, result) in results {
+        let rows: Vec<(String, String)> = match result {
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| match item {
+                    Value::Array(pair) if pair.len() == 2 => (cell(&pair[0]), cell(&pair[1])),
+                    other => (index.to_string(), cell(other)),
+                })
+                .collect(),
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(key, value)| (key.clone(), cell(value)))
+                .collect(),
+            scalar => vec![(String::new(), cell(scalar))],
+        };
+        for (key, value) in rows {
+            writer
+                .write_record([name.as_str(), key.as_str(), value.as_str()])
+                .map_err(csv_error)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_results(args: &Args, results: &Map<String, Value>) -> Result<(), Error> {
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(io::BufWriter::new(File::create(Path::new(path))?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    match args.output_format {
+        OutputFormat::Json => write_json(&mut { out }, results),
+        OutputFormat::Csv => write_csv(out, results),
+    }
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let result = load(&args).and_then(|dataset| {
+        let mut results = Map::new();
+        for &analysis in &args.analyses {
+            results.insert(analysis_name(analysis), run(&dataset, analysis, &args)?);
+        }
+        write_results(&args, &results)
+    });
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Per-node quantities that several metrics share, computed the first time
+//! one of them asks and kept with the dataset. Snapshots store what is
+//! computed so far.
+//!
+//! A dataset never changes its graph in place: edits and time windows build
+//! a new [`Dataset`] with an empty cache, so a cache never outlives the
+//! graph it was computed on.
+
+use crate::community_detection;
+use crate::dataset::{Dataset, DegreeMode};
+use crate::error::Error;
+use crate::functions;
+use crate::graph::Graph;
+use crate::progress::Control;
+use crate::triangles;
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+
+/// Degree, triangle count and local clustering coefficient of every node of
+/// one view of the graph, by node index. Self-loops are left out of the
+/// degrees. In the in and out views of a directed graph, the triangles of a
+/// node are the pairs of its in- or out-neighbours joined either way.
+#[derive(Debug, Default)]
+pub struct NodeMetrics {
+    pub degrees: Vec<usize>,
+    pub triangles: Vec<usize>,
+    pub clustering: Vec<f64>,
+}
+
+impl NodeMetrics {
+    pub fn compute(
+        dataset: &Dataset,
+        degree: DegreeMode,
+        control: &Control,
+    ) -> Result<Self, Error> {
+        let view = dataset.view(degree);
+        let triangles = if dataset.is_directed() && degree != DegreeMode::Total {
+            triangles::count_in_view(&*dataset.view(DegreeMode::Total), &*view, control)?
+        } else {
+            functions::get_triangles_with(&*view, control)?
+        };
+        let degrees: Vec<usize> = view
+            .nodes()
+            .map(|node| triangles::simple_degree(&*view, node))
+            .collect();
+        let clustering = triangles
+            .iter()
+            .zip(&degrees)
+            .map(|(&triangles, &degree)| functions::cl_coef(triangles, degree))
+            .collect();
+        Ok(NodeMetrics {
+            degrees,
+            triangles,
+            clustering,
+        })
+    }
+}
+
+/// A value computed at most once. Callers asking while it is computed wait
+/// for it instead of computing it again.
+struct Slot<T>(Mutex<Option<Arc<T>>>);
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Slot(Mutex::new(None))
+    }
+}
+
+/// A clone shares the value once it is computed. It starts empty while the
+/// value is still being computed, so cloning never waits for a computation.
+impl<T> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        Slot(Mutex::new(self.peek()))
+    }
+}
+
+impl<T> Slot<T> {
+    fn filled(value: T) -> Self {
+        Slot(Mutex::new(Some(Arc::new(value))))
+    }
+
+    /// The value if it is computed, without waiting for a computation.
+    fn peek(&self) -> Option<Arc<T>> {
+        match self.0.try_lock() {
+            Ok(slot) => slot.clone(),
+            Err(TryLockError::Poisoned(slot)) => slot.into_inner().clone(),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Arc<T>>> {
+        // a panic while computing leaves the slot empty, which is fine
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The value, computed with `compute` if needed. Nothing is kept when
+    /// `compute` fails, so a cancelled computation can be started again.
+    fn get_or_try_init(&self, compute: impl FnOnce() -> Result<T, Error>) -> Result<Arc<T>, Error> {
+        let mut slot = self.lock();
+        if let Some(value) = &*slot {
+            return Ok(value.clone());
+        }
+        let value = Arc::new(compute()?);
+        *slot = Some(value.clone());
+        Ok(value)
+    }
+}
+
+/// Number of [`NodeMetrics`] slots, see [`Dataset::node_metrics_with`].
+pub(crate) const VIEWS: usize = 3;
+
+/// The cached metrics of a dataset.
+#[derive(Clone, Default)]
+pub struct MetricCache {
+    /// By view: total (or undirected), in, out.
+    nodes: [Slot<NodeMetrics>; VIEWS],
+    communities: Slot<Vec<usize>>,
+}
+
+impl MetricCache {
+    /// The node metrics computed so far with their slot, leaving out those
+    /// still being computed.
+    pub(crate) fn computed_nodes(&self) -> Vec<(usize, Arc<NodeMetrics>)> {
+        (0..VIEWS)
+            .filter_map(|slot| Some((slot, self.nodes[slot].peek()?)))
+            .collect()
+    }
+
+    pub(crate) fn computed_communities(&self) -> Option<Arc<Vec<usize>>> {
+        self.communities.peek()
+    }
+
+    /// Stores metrics computed earlier, as read from a snapshot.
+    pub(crate) fn restore_nodes(&mut self, slot: usize, metrics: NodeMetrics) {
+        self.nodes[slot] = Slot::filled(metrics);
+    }
+
+    pub(crate) fn restore_communities(&mut self, communities: Vec<usize>) {
+        self.communities = Slot::filled(communities);
+    }
+}
+
+impl Dataset {
+    /// [`NodeMetrics`] of `view(degree)`, computed on first use.
+    pub fn node_metrics(&self, degree: DegreeMode) -> Arc<NodeMetrics> {
+        self.node_metrics_with(degree, &Control::new())
+            .unwrap_or_default()
+    }
+
+    /// [`Dataset::node_metrics`] reporting the progress of a computation to
+    /// `control`.
+    pub fn node_metrics_with(
+        &self,
+        degree: DegreeMode,
+        control: &Control,
+    ) -> Result<Arc<NodeMetrics>, Error> {
+        let slot = match (self.is_directed(), degree) {
+            (false, _) | (true, DegreeMode::Total) => 0,
+            (true, DegreeMode::In) => 1,
+            (true, DegreeMode::Out) => 2,
+        };
+        self.cache.nodes[slot].get_or_try_init(|| NodeMetrics::compute(self, degree, control))
+    }
+
+    /// Louvain community of every node, computed on first use.
+    pub fn communities(&self) -> Arc<Vec<usize>> {
+        self.communities_with(&Control::new()).unwrap_or_default()
+    }
+
+    /// [`Dataset::communities`] reporting the progress of a computation to
+    /// `control`.
+    pub fn communities_with(&self, control: &Control) -> Result<Arc<Vec<usize>>, Error> {
+        self.cache.communities.get_or_try_init(|| {
+            community_detection::louvain_community_detection_with(
+                &*self.view(DegreeMode::Total),
+                control,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{DatasetBuilder, GraphMode};
+    use crate::edits::{self, Edit};
+    use crate::loader::DuplicatePolicy;
+
+    /// A triangle 1-2-3 with a tail 3-4.
+    fn paw() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        for (from, to) in [(1, 2), (2, 3), (3, 1), (3, 4)] {
+            builder.insert_edge(from, to, 1.0);
+        }
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    #[test]
+    fn computed_once() {
+        let dataset = paw();
+        let metrics = dataset.node_metrics(DegreeMode::Total);
+        assert_eq!(metrics.degrees, vec![2, 2, 3, 1]);
+        assert_eq!(metrics.triangles, vec![1, 1, 1, 0]);
+        assert_eq!(metrics.clustering, vec![1.0, 1.0, 1.0 / 3.0, 0.0]);
+        // undirected graphs share one view
+        assert!(Arc::ptr_eq(&metrics, &dataset.node_metrics(DegreeMode::In)));
+    }
+
+    #[test]
+    fn cancelled_computation_is_not_kept() {
+        let dataset = paw();
+        let control = Control::new();
+        control.cancel();
+        assert!(matches!(
+            dataset.communities_with(&control),
+            Err(Error::Cancelled)
+        ));
+        assert_eq!(dataset.communities().len(), 4);
+    }
+
+    #[test]
+    fn clones_do_not_wait() {
+        let dataset = paw();
+        let communities = dataset
+            .cache
+            .communities
+            .get_or_try_init(|| {
+                // the slot is locked while computing
+                assert!(dataset.clone().cache.communities.lock().is_none());
+                Ok(vec![0; 4])
+            })
+            .unwrap();
+        assert!(Arc::ptr_eq(&dataset.clone().communities(), &communities));
+    }
+
+    #[test]
+    fn edits_invalidate() {
+        let dataset = paw();
+        assert_eq!(dataset.node_metrics(DegreeMode::Total).triangles[0], 1);
+        let (dataset, _) = edits::apply(&dataset, &[Edit::RemoveEdge { from: 1, to: 2 }]).unwrap();
+        assert_eq!(dataset.node_metrics(DegreeMode::Total).triangles[0], 0);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::progress::Control;
+use rayon::prelude::*;
+
+/// Assigns every node a community, returned per node index. Communities are
+/// named after their node of lowest index.
+pub fn louvain_community_detection<G: Graph>(graph: &G) -> Vec<usize> {
+    louvain_community_detection_with(graph, &Control::new()).unwrap_or_default()
+}
+
+/// [`louvain_community_detection`] reporting one unit of progress per node
+/// to `control`, starting over on every pass.
+pub fn louvain_community_detection_with<G: Graph>(
+    graph: &G,
+    control: &Control,
+) -> Result<Vec<usize>, Error> {
+    let mut communities = initialize_communities(graph);
+    let m: f64 = graph.nodes().map(|node| graph.strength(node)).sum::<f64>() / 2.0;
+    if m <= 0.0 {
+        return Ok(communities);
+    }
+    let mut modularity = calculate_modularity(graph, &communities, m);
+    // total strength of every community, kept up to date as nodes move
+    let mut totals: Vec<f64> = graph.nodes().map(|node| graph.strength(node)).collect();
+    let mut links = Links::new(graph.node_count());
+
+    loop {
+        let mut improvement = false;
+
+        control.start(graph.node_count() as u64);
+        for node in graph.nodes() {
+            control.check()?;
+            let current = communities[node];
+            let strength = graph.strength(node);
+            links.collect(graph, node, &communities);
+            totals[current] -= strength;
+            let best = links.best_community(current, strength, &totals, m);
+            totals[best] += strength;
+            if best != current {
+                communities[node] = best;
+                improvement = true;
+            }
+            links.clear();
+            control.advance(1);
+        }
+
+        if !improvement {
+            break;
+        }
+
+        let new_modularity = calculate_modularity(graph, &communities, m);
+        if new_modularity <= modularity {
+            break;
+        }
+        modularity = new_modularity;
+    }
+
+    Ok(relabel(communities))
+}
+
+fn initialize_communities<G: Graph>(graph: &G) -> Vec<usize> {
+    graph.nodes().collect()
+}
+
+/// Newman modularity: the sum over communities of the share of edge weight
+/// inside the community minus the share expected from the node strengths.
+fn calculate_modularity<G: Graph>(graph: &G, communities: &[usize], m: f64) -> f64 {
+    let mut inside = vec![0.0; graph.node_count()];
+    let mut total = vec![0.0; graph.node_count()];
+    for node in graph.nodes() {
+        let community = communities[node];
+        total[community] += graph.strength(node);
+        inside[community] += graph
+            .weighted_neighbors(node)
+            .filter(|&(neighbor, _)| communities[neighbor] == community)
+            .map(|(_, weight)| weight)
+            .sum::<f64>();
+    }
+    inside
+        .into_par_iter()
+        .zip(total)
+        .map(|(inside, total)| inside / (2.0 * m) - (total / (2.0 * m)).powi(2))
+        .sum()
+}
+
+/// The weight of the edges from one node to each community around it.
+struct Links {
+    /// By community, `None` when no neighbour is in it.
+    weights: Vec<Option<f64>>,
+    /// The communities with an entry in `weights`, in the order found.
+    touched: Vec<usize>,
+}
+
+impl Links {
+    fn new(node_count: usize) -> Self {
+        Links {
+            weights: vec![None; node_count],
+            touched: Vec::new(),
+        }
+    }
+
+    /// Sums the weights from `node` to every neighbouring community,
+    /// leaving out self-loops.
+    fn collect<G: Graph>(&mut self, graph: &G, node: usize, communities: &[usize]) {
+        for (neighbor, weight) in graph.weighted_neighbors(node) {
+            if neighbor == node {
+                continue;
+            }
+            let community = communities[neighbor];
+            match &mut self.weights[community] {
+                Some(sum) => *sum += weight,
+                empty => {
+                    *empty = Some(weight);
+                    self.touched.push(community);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        for community in self.touched.drain(..) {
+            self.weights[community] = None;
+        }
+    }
+
+    /// The community a node of the given strength gains the most modularity
+    /// by joining, `current` on ties. `totals` must not include the node.
+    /// Joining `community` gains `links - total * strength / 2m` over
+    /// staying alone, up to a factor of `1 / m`.
+    fn best_community(&self, current: usize, strength: f64, totals: &[f64], m: f64) -> usize {
+        let gain = |community: usize| {
+            self.weights[community].unwrap_or(0.0) - totals[community] * strength / (2.0 * m)
+        };
+        let mut best = current;
+        let mut best_gain = gain(current);
+        for &community in &self.touched {
+            let community_gain = gain(community);
+            if community_gain > best_gain {
+                best = community;
+                best_gain = community_gain;
+            }
+        }
+        best
+    }
+}
+
+/// Names every community after its node of lowest index.
+fn relabel(communities: Vec<usize>) -> Vec<usize> {
+    let mut names = vec![usize::MAX; communities.len()];
+    communities
+        .into_iter()
+        .enumerate()
+        .map(|(node, community)| {
+            if names[community] == usize::MAX {
+                names[community] = node;
+            }
+            names[community]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+
+    #[test]
+    fn separates_two_triangles() {
+        let graph = Csr::unweighted(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+        let communities = louvain_community_detection(&graph);
+        assert_eq!(communities[0], communities[1]);
+        assert_eq!(communities[1], communities[2]);
+        assert_eq!(communities[3], communities[4]);
+        assert_eq!(communities[4], communities[5]);
+        assert_ne!(communities[0], communities[3]);
+    }
+
+    #[test]
+    fn modularity_of_two_triangles() {
+        let graph = Csr::unweighted(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+        let m = 7.0;
+        // 6 of the 14 edge ends inside each triangle, 7 of the 14 strength units
+        let expected = 2.0 * (6.0 / 14.0 - 0.25);
+        let split = [0, 0, 0, 3, 3, 3];
+        assert!((calculate_modularity(&graph, &split, m) - expected).abs() < 1e-12);
+        assert!((calculate_modularity(&graph, &[0; 6], m)).abs() < 1e-12);
+        let found = louvain_community_detection(&graph);
+        assert!((calculate_modularity(&graph, &found, m) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ring_of_cliques() {
+        // 8 cliques of 5 nodes, each joined to the next by one edge
+        let mut edges = Vec::new();
+        for clique in 0..8 {
+            let first = clique * 5;
+            for a in first..first + 5 {
+                for b in a + 1..first + 5 {
+                    edges.push((a, b));
+                }
+            }
+            edges.push((first + 4, (first + 5) % 40));
+        }
+        let communities = louvain_community_detection(&Csr::unweighted(40, &edges));
+        let expected: Vec<usize> = (0..40).map(|node| node / 5 * 5).collect();
+        assert_eq!(communities, expected);
+    }
+
+    #[test]
+    fn names_communities_after_their_first_node() {
+        let graph = Csr::unweighted(4, &[(0, 1), (2, 3)]);
+        let communities = louvain_community_detection(&graph);
+        for (node, &community) in communities.iter().enumerate() {
+            assert_eq!(communities[community], community, "node {}", node);
+            assert!(community <= node);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counts the bytes read from the underlying file, so progress can be
+/// reported against the file size even when it is decompressed.
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+impl Compression {
+    fn from_extension(path: &Path) -> Option<Compression> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "bz2" | "bzip2" => Some(Compression::Bzip2),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    fn from_magic(head: &[u8]) -> Compression {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if head.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// `path` without its compression extension, if it has one.
+pub fn strip_extension(path: &Path) -> PathBuf {
+    match Compression::from_extension(path) {
+        Some(_) => path.with_extension(""),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Opens `path` for streaming, decompressing it on the fly when the extension
+/// or the magic bytes say it is gzip, bzip2 or zstd compressed.
+///
+/// Also returns the path with the compression extension removed, so the
+/// graph format can still be detected from names like `graph.graphml.gz`.
+/// `bytes_read` is kept up to date with the number of bytes read from disk.
+pub fn open(
+    path: &Path,
+    bytes_read: Arc<AtomicU64>,
+) -> Result<(Box<dyn BufRead + Send>, PathBuf), Error> {
+    let mut file = BufReader::new(CountingReader {
+        inner: File::open(path)?,
+        bytes_read,
+    });
+    let compression = match Compression::from_extension(path) {
+        Some(compression) => compression,
+        None => Compression::from_magic(file.fill_buf()?),
+    };
+    let inner_path = strip_extension(path);
+
+    let reader: Box<dyn BufRead + Send> = match compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(file))),
+        Compression::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(file))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(
+            file,
+        )?)),
+    };
+    Ok((reader, inner_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const CONTENT: &str = "1 2\n2 3\n3 1\n";
+
+    fn compress(compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::None => CONTENT.as_bytes().to_vec(),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(CONTENT.as_bytes()).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(CONTENT.as_bytes()).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(CONTENT.as_bytes(), 0).unwrap(),
+        }
+    }
+
+    fn read_back(path: &Path) -> (String, PathBuf, u64) {
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let (mut reader, inner_path) = open(path, bytes_read.clone()).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        (content, inner_path, bytes_read.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn round_trip() {
+        for (compression, extension) in [
+            (Compression::None, "txt"),
+            (Compression::Gzip, "gz"),
+            (Compression::Bzip2, "bz2"),
+            (Compression::Zstd, "zst"),
+        ] {
+            let compressed = compress(compression);
+            let path = crate::temp_path(&format!("compression.edges.{}", extension));
+            std::fs::write(&path, &compressed).unwrap();
+            let (content, inner_path, bytes_read) = read_back(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(content, CONTENT, "{:?}", compression);
+            assert_eq!(bytes_read, compressed.len() as u64);
+            if compression != Compression::None {
+                assert_eq!(inner_path.extension().unwrap(), "edges");
+            }
+        }
+    }
+
+    #[test]
+    fn detects_magic_bytes() {
+        for compression in [Compression::Gzip, Compression::Bzip2, Compression::Zstd] {
+            let compressed = compress(compression);
+            assert_eq!(Compression::from_magic(&compressed), compression);
+            let path = crate::temp_path(&format!("magic-{:?}.txt", compression));
+            std::fs::write(&path, &compressed).unwrap();
+            let (content, inner_path, _) = read_back(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(content, CONTENT);
+            assert_eq!(inner_path, path);
+        }
+        assert_eq!(
+            Compression::from_magic(CONTENT.as_bytes()),
+            Compression::None
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = crate::temp_path("missing.gz");
+        assert!(matches!(
+            open(&path, Arc::new(AtomicU64::new(0))),
+            Err(Error::Io { .. })
+        ));
+    }
+}
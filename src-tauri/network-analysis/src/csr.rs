@@ -0,0 +1,254 @@
+use crate::graph::Graph;
+
+/// Compressed sparse row adjacency: the neighbours of node `i` are
+/// `targets[offsets[i]..offsets[i + 1]]`, sorted in ascending order.
+/// Weights are only stored when at least one edge does not weigh 1.
+#[derive(Clone, Debug)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+    weights: Option<Vec<f64>>,
+}
+
+impl Csr {
+    /// Builds the adjacency from arcs sorted by `(from, to)` without duplicates.
+    ///
+    /// With `symmetric` every arc is also stored in the opposite direction, which
+    /// is how undirected edges are kept; the arcs must then have `from <= to`.
+    /// With `reverse` only the opposite direction is stored, giving the in-adjacency.
+    pub fn from_sorted_arcs(
+        node_count: usize,
+        arcs: &[(u32, u32, f64)],
+        symmetric: bool,
+        reverse: bool,
+    ) -> Self {
+        let mut offsets = vec![0; node_count + 1];
+        for &(from, to, _) in arcs {
+            if reverse {
+                offsets[to as usize + 1] += 1;
+            } else {
+                offsets[from as usize + 1] += 1;
+                if symmetric && from != to {
+                    offsets[to as usize + 1] += 1;
+                }
+            }
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+        }
+
+        let weighted = arcs.iter().any(|&(_, _, weight)| weight != 1.0);
+        let mut targets = vec![0; offsets[node_count]];
+        let mut weights = if weighted {
+            vec![0.0; offsets[node_count]]
+        } else {
+            Vec::new()
+        };
+        let mut cursor = offsets[..node_count].to_vec();
+        let mut place = |from: u32, to: u32, weight: f64| {
+            let position = cursor[from as usize];
+            targets[position] = to;
+            if weighted {
+                weights[position] = weight;
+            }
+            cursor[from as usize] += 1;
+        };
+        // Walking the arcs in order fills every row in ascending order: a row
+        // first receives the reversed arcs, whose sources are all smaller.
+        for &(from, to, weight) in arcs {
+            if reverse {
+                place(to, from, weight);
+            } else {
+                place(from, to, weight);
+                if symmetric && from != to {
+                    place(to, from, weight);
+                }
+            }
+        }
+
+        Csr {
+            offsets,
+            targets,
+            weights: if weighted { Some(weights) } else { None },
+        }
+    }
+
+    /// Rebuilds an adjacency from the arrays returned by [`Csr::raw_parts`],
+    /// or `None` when they do not describe a valid graph.
+    pub fn from_raw_parts(
+        offsets: Vec<usize>,
+        targets: Vec<u32>,
+        weights: Option<Vec<f64>>,
+    ) -> Option<Csr> {
+        let node_count = offsets.len().checked_sub(1)?;
+        let valid = offsets[0] == 0
+            && offsets[node_count] == targets.len()
+            && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+            && targets.iter().all(|&target| (target as usize) < node_count)
+            && weights.as_ref().map_or(true, |w| w.len() == targets.len());
+        if !valid {
+            return None;
+        }
+        Some(Csr {
+            offsets,
+            targets,
+            weights,
+        })
+    }
+
+    pub fn raw_parts(&self) -> (&[usize], &[u32], Option<&[f64]>) {
+        (&self.offsets, &self.targets, self.weights.as_deref())
+    }
+
+    /// Merges two adjacencies over the same nodes, summing the weights of
+    /// entries present in both.
+    pub fn union(&self, other: &Csr) -> Csr {
+        let node_count = self.node_count();
+        let mut offsets = Vec::with_capacity(node_count + 1);
+        let mut targets = Vec::with_capacity(self.targets.len() + other.targets.len());
+        let mut weights = Vec::new();
+        offsets.push(0);
+        for node in 0..node_count {
+            let mut left = self.weighted_neighbors(node).peekable();
+            let mut right = other.weighted_neighbors(node).peekable();
+            loop {
+                let next = match (left.peek(), right.peek()) {
+                    (Some(&(a, wa)), Some(&(b, wb))) => {
+                        if a < b {
+                            left.next();
+                            (a, wa)
+                        } else if b < a {
+                            right.next();
+                            (b, wb)
+                        } else {
+                            left.next();
+                            right.next();
+                            (a, wa + wb)
+                        }
+                    }
+                    (Some(_), None) => left.next().unwrap(),
+                    (None, Some(_)) => right.next().unwrap(),
+                    (None, None) => break,
+                };
+                targets.push(next.0 as u32);
+                weights.push(next.1);
+            }
+            offsets.push(targets.len());
+        }
+        // reciprocal arcs sum to 2, so the merged graph may be weighted
+        let weighted = weights.iter().any(|&weight| weight != 1.0);
+        Csr {
+            offsets,
+            targets,
+            weights: if weighted { Some(weights) } else { None },
+        }
+    }
+}
+
+#[cfg(test)]
+impl Csr {
+    /// An undirected graph with the given weighted edges, in any order.
+    pub(crate) fn from_edges(node_count: usize, edges: &[(u32, u32, f64)]) -> Csr {
+        let mut arcs: Vec<(u32, u32, f64)> = edges
+            .iter()
+            .map(|&(from, to, weight)| (from.min(to), from.max(to), weight))
+            .collect();
+        arcs.sort_by_key(|&(from, to, _)| (from, to));
+        Csr::from_sorted_arcs(node_count, &arcs, true, false)
+    }
+
+    /// An undirected graph with the given edges of weight 1.
+    pub(crate) fn unweighted(node_count: usize, edges: &[(u32, u32)]) -> Csr {
+        let edges: Vec<(u32, u32, f64)> = edges.iter().map(|&(from, to)| (from, to, 1.0)).collect();
+        Csr::from_edges(node_count, &edges)
+    }
+}
+
+impl Graph for Csr {
+    fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn neighbors(&self, node: usize) -> &[u32] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    fn weights(&self, node: usize) -> Option<&[f64]> {
+        self.weights
+            .as_ref()
+            .map(|weights| &weights[self.offsets[node]..self.offsets[node + 1]])
+    }
+
+    fn arc_count(&self) -> usize {
+        self.targets.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The arcs 0 -> 1, 0 -> 2 (weight 2) and 2 -> 1.
+    fn arcs() -> Vec<(u32, u32, f64)> {
+        vec![(0, 1, 1.0), (0, 2, 2.0), (2, 1, 1.0)]
+    }
+
+    #[test]
+    fn directions() {
+        let out = Csr::from_sorted_arcs(3, &arcs(), false, false);
+        assert_eq!(out.neighbors(0), &[1, 2]);
+        assert_eq!(out.neighbors(1), &[] as &[u32]);
+        assert_eq!(out.weight(0, 2), Some(2.0));
+        assert_eq!(out.weight(2, 0), None);
+
+        let incoming = Csr::from_sorted_arcs(3, &arcs(), false, true);
+        assert_eq!(incoming.neighbors(1), &[0, 2]);
+        assert_eq!(incoming.weights(2), Some(&[2.0][..]));
+
+        let symmetric = Csr::from_edges(3, &[(0, 1, 1.0), (0, 2, 2.0), (1, 2, 1.0), (2, 2, 1.0)]);
+        assert_eq!(symmetric.neighbors(2), &[0, 1, 2]);
+        assert_eq!(symmetric.arc_count(), 7);
+        assert_eq!(symmetric.strength(2), 4.0);
+    }
+
+    #[test]
+    fn unweighted_graphs_store_no_weights() {
+        let graph = Csr::unweighted(3, &[(0, 1), (1, 2)]);
+        assert!(graph.weights(1).is_none());
+        assert_eq!(
+            graph.weighted_neighbors(1).collect::<Vec<_>>(),
+            vec![(0, 1.0), (2, 1.0)]
+        );
+    }
+
+    #[test]
+    fn union_sums_shared_entries() {
+        let out = Csr::from_sorted_arcs(3, &arcs(), false, false);
+        let incoming = Csr::from_sorted_arcs(3, &arcs(), false, true);
+        let total = out.union(&incoming);
+        assert_eq!(total.neighbors(1), &[0, 2]);
+        assert_eq!(total.weight(0, 2), Some(2.0));
+
+        let both = out.union(&out);
+        assert_eq!(both.weight(0, 1), Some(2.0));
+    }
+
+    #[test]
+    fn raw_parts_round_trip() {
+        let graph = Csr::from_sorted_arcs(3, &arcs(), false, false);
+        let (offsets, targets, weights) = graph.raw_parts();
+        let copy = Csr::from_raw_parts(
+            offsets.to_vec(),
+            targets.to_vec(),
+            weights.map(<[f64]>::to_vec),
+        )
+        .unwrap();
+        assert_eq!(copy.raw_parts(), graph.raw_parts());
+
+        // a target past the last node, and offsets that do not cover the targets
+        assert!(Csr::from_raw_parts(vec![0, 1], vec![1], None).is_none());
+        assert!(Csr::from_raw_parts(vec![0, 1, 1], vec![1, 0], None).is_none());
+        assert!(Csr::from_raw_parts(vec![0, 1, 2], vec![1, 0], Some(vec![1.0])).is_none());
+        assert!(Csr::from_raw_parts(Vec::new(), Vec::new(), None).is_none());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::attributes::NodeAttributes;
+use crate::cache::MetricCache;
+use crate::csr::Csr;
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::labels::NodeLabels;
+use crate::loader::{DuplicatePolicy, LoadReport};
+use crate::sampling::SampleInfo;
+use crate::temporal::{TimedEdge, Timeline};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphMode {
+    Undirected,
+    Directed,
+}
+
+/// Which neighbours of a node to look at when the graph is directed.
+/// Undirected graphs have a single neighbourhood and ignore this.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DegreeMode {
+    #[default]
+    Total,
+    In,
+    Out,
+}
+
+#[derive(Clone)]
+pub struct Dataset {
+    /// Out-adjacency for directed graphs, the symmetric adjacency otherwise.
+    pub graph: Csr,
+    /// In-adjacency, only kept for directed graphs.
+    pub incoming: Option<Csr>,
+    /// The ID from the input file of every node index, in ascending order.
+    pub ids: Vec<usize>,
+    pub mode: GraphMode,
+    pub labels: NodeLabels,
+    pub attributes: NodeAttributes,
+    /// Edge timestamps, only kept for temporal datasets.
+    pub timeline: Option<Timeline>,
+    /// Set when the dataset was read from a file.
+    pub report: Option<LoadReport>,
+    /// Set when the dataset is a sample of another one.
+    pub sample: Option<SampleInfo>,
+    /// Number of edits applied since the dataset was loaded.
+    pub revision: u64,
+    /// Metrics computed so far, see [`crate::cache`].
+    pub(crate) cache: MetricCache,
+}
+
+impl Dataset {
+    pub fn is_directed(&self) -> bool {
+        self.mode == GraphMode::Directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Number of edges, or of arcs for a directed graph.
+    pub fn edge_count(&self) -> usize {
+        let entries = self.graph.arc_count();
+        if self.is_directed() {
+            return entries;
+        }
+        let self_loops = self
+            .graph
+            .nodes()
+            .filter(|&node| self.graph.has_edge(node, node))
+            .count();
+        (entries + self_loops) / 2
+    }
+
+    /// The node index of the node with the given ID.
+    pub fn index(&self, id: usize) -> Option<usize> {
+        self.ids.binary_search(&id).ok()
+    }
+
+    /// The ID of the node at `index`.
+    pub fn id(&self, index: usize) -> usize {
+        self.ids[index]
+    }
+
+    /// The node index of `id`, or [`Error::UnknownNode`] when the graph has no such node.
+    pub fn ensure_node(&self, id: usize) -> Result<usize, Error> {
+        self.index(id).ok_or(Error::UnknownNode { node: id })
+    }
+
+    /// Number of nodes without any edge in either direction.
+    pub fn isolated_node_count(&self) -> usize {
+        self.graph
+            .nodes()
+            .filter(|&node| {
+                self.graph.degree(node) == 0
+                    && self
+                        .incoming
+                        .as_ref()
+                        .map_or(true, |incoming| incoming.degree(node) == 0)
+            })
+            .count()
+    }
+
+    /// The adjacency the metrics should run on for the requested neighbourhood.
+    /// For a directed graph `Total` merges both directions, summing the weights
+    /// of reciprocal edges.
+    pub fn view(&self, degree: DegreeMode) -> Cow<'_, Csr> {
+        let incoming = match &self.incoming {
+            Some(incoming) => incoming,
+            None => return Cow::Borrowed(&self.graph),
+        };
+        match degree {
+            DegreeMode::Out => Cow::Borrowed(&self.graph),
+            DegreeMode::In => Cow::Borrowed(incoming),
+            DegreeMode::Total => Cow::Owned(self.graph.union(incoming)),
+        }
+    }
+}
+
+/// Collects nodes and edges with arbitrary IDs and turns them into a [`Dataset`].
+pub struct DatasetBuilder {
+    mode: GraphMode,
+    nodes: Vec<usize>,
+    arcs: Vec<(usize, usize, f64)>,
+    /// The timestamp of every arc, when the edges are timed.
+    times: Vec<i64>,
+    pub labels: NodeLabels,
+}
+
+impl DatasetBuilder {
+    pub fn new(mode: GraphMode) -> Self {
+        DatasetBuilder {
+            mode,
+            nodes: Vec::new(),
+            arcs: Vec::new(),
+            times: Vec::new(),
+            labels: NodeLabels::new(),
+        }
+    }
+
+    /// Adds a node, which is kept even when it has no edges.
+    pub fn insert_node(&mut self, node: usize) {
+        self.nodes.push(node);
+    }
+
+    /// Adds an edge. Undirected graphs store it in both directions.
+    pub fn insert_edge(&mut self, from: usize, to: usize, weight: f64) {
+        self.arcs.push((from, to, weight));
+    }
+
+    /// Adds an edge seen at `time`. A dataset built from timed edges keeps
+    /// them in its [`Timeline`]; all edges should then be timed.
+    pub fn insert_timed_edge(&mut self, from: usize, to: usize, weight: f64, time: i64) {
+        self.arcs.push((from, to, weight));
+        self.times.push(time);
+    }
+
+    /// Remaps the node IDs to dense indices and builds the adjacency, merging
+    /// repeated edges according to `duplicates`. Also returns how many edges
+    /// were merged into an earlier one.
+    pub fn build(self, duplicates: DuplicatePolicy) -> Result<(Dataset, usize), Error> {
+        let undirected = self.mode == GraphMode::Undirected;
+        let mut ids = self.nodes;
+        ids.extend(self.arcs.iter().flat_map(|&(from, to, _)| [from, to]));
+        ids.par_sort_unstable();
+        ids.dedup();
+        if ids.len() > u32::MAX as usize {
+            return Err(Error::InvalidFormat {
+                message: format!("{} nodes is more than the supported maximum", ids.len()),
+            });
+        }
+
+        let index = |id: usize| ids.binary_search(&id).unwrap() as u32;
+        let timeline = if self.times.len() == self.arcs.len() && !self.times.is_empty() {
+            let mut edges: Vec<TimedEdge> = self
+                .arcs
+                .par_iter()
+                .zip(self.times.par_iter())
+                .map(|(&(from, to, weight), &time)| {
+                    let (from, to) = (index(from), index(to));
+                    let (from, to) = if undirected && from > to {
+                        (to, from)
+                    } else {
+                        (from, to)
+                    };
+                    TimedEdge {
+                        from,
+                        to,
+                        time,
+                        weight,
+                    }
+                })
+                .collect();
+            edges.par_sort_by_key(|edge| edge.time);
+            Some(Timeline { edges })
+        } else {
+            None
+        };
+        let mut arcs: Vec<(u32, u32, f64)> = self
+            .arcs
+            .into_par_iter()
+            .map(|(from, to, weight)| {
+                let (from, to) = (index(from), index(to));
+                if undirected && from > to {
+                    (to, from, weight)
+                } else {
+                    (from, to, weight)
+                }
+            })
+            .collect();
+        // stable, so `DuplicatePolicy::First` keeps the edge that came first
+        arcs.par_sort_by_key(|&(from, to, _)| (from, to));
+
+        let mut merged = 0;
+        let mut kept = 0;
+        for position in 0..arcs.len() {
+            let (from, to, weight) = arcs[position];
+            if kept > 0 && arcs[kept - 1].0 == from && arcs[kept - 1].1 == to {
+                arcs[kept - 1].2 = duplicates.merge(arcs[kept - 1].2, weight);
+                merged += 1;
+            } else {
+                arcs[kept] = arcs[position];
+                kept += 1;
+            }
+        }
+        arcs.truncate(kept);
+
+        let graph = Csr::from_sorted_arcs(ids.len(), &arcs, undirected, false);
+        let incoming = if undirected {
+            None
+        } else {
+            Some(Csr::from_sorted_arcs(ids.len(), &arcs, false, true))
+        };
+        let dataset = Dataset {
+            graph,
+            incoming,
+            ids,
+            mode: self.mode,
+            labels: self.labels,
+            attributes: NodeAttributes::default(),
+            timeline,
+            report: None,
+            sample: None,
+            revision: 0,
+            cache: MetricCache::default(),
+        };
+        Ok((dataset, merged))
+    }
+}
@@ -0,0 +1,406 @@
+//! What-if changes to a loaded dataset. Node IDs are the IDs from the input
+//! file, and new nodes get the IDs they are added with.
+
+use crate::dataset::{Dataset, DatasetBuilder};
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::loader::DuplicatePolicy;
+use crate::temporal::{TimedEdge, Timeline};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Edit {
+    /// Adds a node without edges. Adding an existing node does nothing.
+    AddNode { node: usize },
+    /// Removes a node and all its edges.
+    RemoveNode { node: usize },
+    /// Adds an edge, creating its end nodes if needed. The weight defaults to
+    /// 1 and replaces the weight of an existing edge. Like when loading, it
+    /// must be finite and not negative.
+    AddEdge {
+        from: usize,
+        to: usize,
+        weight: Option<f64>,
+    },
+    /// Removes an edge, keeping its end nodes.
+    RemoveEdge { from: usize, to: usize },
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EditSummary {
+    pub nodes_added: usize,
+    pub nodes_removed: usize,
+    pub edges_added: usize,
+    pub edges_removed: usize,
+    /// The revision of the dataset after the edits.
+    pub revision: u64,
+}
+
+/// The edits of a batch, relative to the dataset they apply to. Only what
+/// the edits touch is stored; the adjacency of the dataset is read in place.
+struct Changes<'a> {
+    dataset: &'a Dataset,
+    /// Nodes of the dataset that were removed. Their edges are gone even if
+    /// the node is added again.
+    removed: HashSet<usize>,
+    /// Nodes that are not in the dataset, or that were removed and added again.
+    added: BTreeSet<usize>,
+    /// Edges added (with their weight) or removed, by [`Changes::key`].
+    edges: HashMap<(usize, usize), Option<f64>>,
+    /// The other end of every entry of `edges`, by node. May hold stale
+    /// entries, which `edges` tells apart.
+    partners: HashMap<usize, Vec<usize>>,
+}
+
+impl<'a> Changes<'a> {
+    fn new(dataset: &'a Dataset) -> Self {
+        Changes {
+            dataset,
+            removed: HashSet::new(),
+            added: BTreeSet::new(),
+            edges: HashMap::new(),
+            partners: HashMap::new(),
+        }
+    }
+
+    fn key(&self, from: usize, to: usize) -> (usize, usize) {
+        if self.dataset.is_directed() {
+            (from, to)
+        } else {
+            (from.min(to), from.max(to))
+        }
+    }
+
+    /// Whether `node` is a node of the dataset that has not been removed.
+    fn is_original(&self, node: usize) -> bool {
+        self.dataset.index(node).is_some() && !self.removed.contains(&node)
+    }
+
+    fn contains_node(&self, node: usize) -> bool {
+        self.added.contains(&node) || self.is_original(node)
+    }
+
+    /// Weight of the edge `key` once the changes so far are applied.
+    fn edge(&self, key: (usize, usize)) -> Option<f64> {
+        if let Some(&change) = self.edges.get(&key) {
+            return change;
+        }
+        let (from, to) = key;
+        if !self.is_original(from) || !self.is_original(to) {
+            return None;
+        }
+        let dataset = self.dataset;
+        dataset
+            .graph
+            .weight(dataset.index(from)?, dataset.index(to)?)
+    }
+
+    /// Adds `node` unless it is present, returning whether it was added.
+    fn add_node(&mut self, node: usize) -> bool {
+        !self.contains_node(node) && self.added.insert(node)
+    }
+
+    /// Removes `node` and returns the number of edges removed with it.
+    fn remove_node(&mut self, node: usize) -> Result<usize, Error> {
+        if !self.contains_node(node) {
+            return Err(Error::UnknownNode { node });
+        }
+        let mut removed_edges = 0;
+        if self.is_original(node) {
+            let dataset = self.dataset;
+            let index = dataset.index(node).unwrap();
+            let outgoing = dataset.graph.neighbors(index).iter().map(|&to| (index, to));
+            // in-neighbours of a directed graph, self-loops already counted above
+            let incoming = dataset.incoming.iter().flat_map(|incoming| {
+                incoming
+                    .neighbors(index)
+                    .iter()
+                    .filter(move |&&from| from as usize != index)
+                    .map(move |&from| (from as usize, index as u32))
+            });
+            for (from, to) in outgoing.chain(incoming) {
+                let (from, to) = (dataset.id(from), dataset.id(to as usize));
+                let unchanged = !self.edges.contains_key(&self.key(from, to));
+                if unchanged && self.is_original(from) && self.is_original(to) {
+                    removed_edges += 1;
+                }
+            }
+            self.removed.insert(node);
+        }
+        self.added.remove(&node);
+        for partner in self.partners.remove(&node).unwrap_or_default() {
+            for key in [self.key(node, partner), self.key(partner, node)] {
+                if let Some(Some(_)) = self.edges.remove(&key) {
+                    removed_edges += 1;
+                }
+            }
+        }
+        Ok(removed_edges)
+    }
+
+    /// Sets the weight of the edge from `from` to `to`, returning whether it
+    /// is a new edge.
+    fn add_edge(&mut self, from: usize, to: usize, weight: f64) -> bool {
+        let key = self.key(from, to);
+        let new = self.edge(key).is_none();
+        self.edges.insert(key, Some(weight));
+        self.partners.entry(from).or_default().push(to);
+        self.partners.entry(to).or_default().push(from);
+        new
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> Result<(), Error> {
+        let key = self.key(from, to);
+        if self.edge(key).is_none() {
+            return Err(Error::InvalidArgument {
+                message: format!("there is no edge from {} to {}", from, to),
+            });
+        }
+        self.edges.insert(key, None);
+        Ok(())
+    }
+
+    /// The edited graph: the edges of the dataset between remaining nodes
+    /// that no edit touched, in one pass, then the added ones.
+    fn build(&self) -> Result<Dataset, Error> {
+        let dataset = self.dataset;
+        let mut builder = DatasetBuilder::new(dataset.mode);
+        for &node in &dataset.ids {
+            if !self.removed.contains(&node) {
+                builder.insert_node(node);
+            }
+        }
+        for &node in &self.added {
+            builder.insert_node(node);
+        }
+        for from in dataset.graph.nodes() {
+            let from_id = dataset.id(from);
+            if self.removed.contains(&from_id) {
+                continue;
+            }
+            for (to, weight) in dataset.graph.weighted_neighbors(from) {
+                let to_id = dataset.id(to);
+                // undirected edges are stored from both ends
+                let once = dataset.is_directed() || from <= to;
+                if once
+                    && !self.removed.contains(&to_id)
+                    && !self.edges.contains_key(&self.key(from_id, to_id))
+                {
+                    builder.insert_edge(from_id, to_id, weight);
+                }
+            }
+        }
+        for (&(from, to), &change) in &self.edges {
+            if let Some(weight) = change {
+                builder.insert_edge(from, to, weight);
+            }
+        }
+        Ok(builder.build(DuplicatePolicy::First)?.0)
+    }
+}
+
+/// Applies `edits` in order to a copy of `dataset`, rebuilding the adjacency
+/// once, and returns the edited dataset. Either all edits apply or, on the
+/// first one that cannot, the error is returned.
+///
+/// Labels and attributes are kept for the remaining nodes; added nodes have
+/// no attribute values. The timeline keeps the timed edges that remain, so
+/// added edges are not part of it.
+pub fn apply(dataset: &Dataset, edits: &[Edit]) -> Result<(Dataset, EditSummary), Error> {
+    let mut changes = Changes::new(dataset);
+    let mut summary = EditSummary::default();
+    for edit in edits {
+        match *edit {
+            Edit::AddNode { node } => {
+                if changes.add_node(node) {
+                    summary.nodes_added += 1;
+                }
+            }
+            Edit::RemoveNode { node } => {
+                summary.edges_removed += changes.remove_node(node)?;
+                summary.nodes_removed += 1;
+            }
+            Edit::AddEdge { from, to, weight } => {
+                let weight = weight.unwrap_or(1.0);
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(Error::InvalidArgument {
+                        message: format!("edge weight {} is negative or not finite", weight),
+                    });
+                }
+                for node in [from, to] {
+                    if changes.add_node(node) {
+                        summary.nodes_added += 1;
+                    }
+                }
+                if changes.add_edge(from, to, weight) {
+                    summary.edges_added += 1;
+                }
+            }
+            Edit::RemoveEdge { from, to } => {
+                changes.remove_edge(from, to)?;
+                summary.edges_removed += 1;
+            }
+        }
+    }
+
+    let mut edited = changes.build()?;
+    let sources: Vec<Option<usize>> = edited.ids.iter().map(|&id| dataset.index(id)).collect();
+    edited.attributes = dataset.attributes.select(&sources);
+    edited.timeline = dataset.timeline.as_ref().map(|timeline| Timeline {
+        edges: timeline
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let (from, to) = (dataset.id(edge.from as usize), dataset.id(edge.to as usize));
+                changes.edge(changes.key(from, to))?;
+                Some(TimedEdge {
+                    from: edited.index(from)? as u32,
+                    to: edited.index(to)? as u32,
+                    ..*edge
+                })
+            })
+            .collect(),
+    });
+    edited.labels = dataset.labels.clone();
+    edited.report = dataset.report.clone();
+    edited.sample = dataset.sample.clone();
+    edited.revision = dataset.revision + 1;
+    summary.revision = edited.revision;
+    Ok((edited, summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::GraphMode;
+    use crate::path;
+
+    /// A star around 1 with leaves 2, 3 and 4, and an edge 2 - 3.
+    fn star() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        for leaf in 2..5 {
+            builder.insert_edge(1, leaf, 1.0);
+        }
+        builder.insert_edge(2, 3, 1.0);
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    #[test]
+    fn removing_the_hub_disconnects_the_leaves() {
+        let (dataset, summary) = apply(&star(), &[Edit::RemoveNode { node: 1 }]).unwrap();
+        assert_eq!((summary.nodes_removed, summary.edges_removed), (1, 3));
+        assert_eq!(summary.revision, 1);
+        assert_eq!(dataset.ids, vec![2, 3, 4]);
+        let (two, four) = (dataset.index(2).unwrap(), dataset.index(4).unwrap());
+        assert_eq!(path::dijkstra(&dataset.graph, two, four), None);
+    }
+
+    #[test]
+    fn batch_applies_in_order() {
+        let dataset = star();
+        let edits = [
+            Edit::AddEdge {
+                from: 4,
+                to: 5,
+                weight: Some(2.0),
+            },
+            Edit::RemoveEdge { from: 3, to: 2 },
+            Edit::AddNode { node: 9 },
+        ];
+        let (dataset, summary) = apply(&dataset, &edits).unwrap();
+        assert_eq!((summary.nodes_added, summary.edges_added), (2, 1));
+        assert_eq!(dataset.ids, vec![1, 2, 3, 4, 5, 9]);
+        assert_eq!(dataset.edge_count(), 4);
+        let (four, five) = (dataset.index(4).unwrap(), dataset.index(5).unwrap());
+        assert_eq!(dataset.graph.weight(five, four), Some(2.0));
+    }
+
+    #[test]
+    fn edits_see_earlier_edits() {
+        let dataset = star();
+        let edits = [
+            Edit::AddEdge {
+                from: 4,
+                to: 5,
+                weight: None,
+            },
+            // takes the new edge 4 - 5 with it
+            Edit::RemoveNode { node: 4 },
+            // back without its old edge to 1
+            Edit::AddEdge {
+                from: 4,
+                to: 2,
+                weight: Some(3.0),
+            },
+            Edit::RemoveNode { node: 1 },
+        ];
+        let (dataset, summary) = apply(&dataset, &edits).unwrap();
+        assert_eq!((summary.nodes_added, summary.nodes_removed), (2, 2));
+        assert_eq!((summary.edges_added, summary.edges_removed), (2, 4));
+        assert_eq!(dataset.ids, vec![2, 3, 4, 5]);
+        assert_eq!(dataset.edge_count(), 2);
+        let (two, four) = (dataset.index(2).unwrap(), dataset.index(4).unwrap());
+        assert_eq!(dataset.graph.weight(four, two), Some(3.0));
+        assert!(matches!(
+            apply(&dataset, &[Edit::RemoveEdge { from: 1, to: 2 }]),
+            Err(Error::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn directed_edits_keep_the_timeline() {
+        let mut builder = DatasetBuilder::new(GraphMode::Directed);
+        for (from, to, time) in [(1, 2, 0), (2, 1, 1), (2, 3, 2), (3, 3, 3)] {
+            builder.insert_timed_edge(from, to, 1.0, time);
+        }
+        let dataset = builder.build(DuplicatePolicy::First).unwrap().0;
+        let edits = [
+            Edit::RemoveEdge { from: 1, to: 2 },
+            Edit::RemoveNode { node: 3 },
+        ];
+        let (dataset, summary) = apply(&dataset, &edits).unwrap();
+        // 2 -> 3 and the self-loop on 3
+        assert_eq!(summary.edges_removed, 3);
+        assert_eq!(dataset.edge_count(), 1);
+        let (one, two) = (dataset.index(1).unwrap(), dataset.index(2).unwrap());
+        assert!(dataset.graph.has_edge(two, one));
+        assert_eq!(
+            dataset.incoming.as_ref().unwrap().neighbors(one),
+            &[two as u32]
+        );
+        let times: Vec<i64> = dataset
+            .timeline
+            .unwrap()
+            .edges
+            .iter()
+            .map(|e| e.time)
+            .collect();
+        assert_eq!(times, vec![1]);
+    }
+
+    #[test]
+    fn failed_batch_changes_nothing() {
+        let dataset = star();
+        let edits = [
+            Edit::RemoveNode { node: 1 },
+            Edit::RemoveEdge { from: 2, to: 4 },
+        ];
+        assert!(apply(&dataset, &edits).is_err());
+        for weight in [-1.0, f64::NAN] {
+            let edit = Edit::AddEdge {
+                from: 2,
+                to: 4,
+                weight: Some(weight),
+            };
+            assert!(matches!(
+                apply(&dataset, &[edit]),
+                Err(Error::InvalidArgument { .. })
+            ));
+        }
+        assert_eq!(dataset.node_count(), 4);
+        assert_eq!(dataset.edge_count(), 4);
+        assert_eq!(dataset.revision, 0);
+    }
+}
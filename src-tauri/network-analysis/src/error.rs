@@ -0,0 +1,54 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Errors of loading and analysis. Serialized as `{ kind, details }`, which
+/// is how the desktop frontend receives them.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
+pub enum Error {
+    Io { message: String },
+    MalformedLine { line: usize, content: String },
+    InvalidFormat { message: String },
+    EmptyGraph,
+    UnknownNode { node: usize },
+    UnknownLabel { label: String },
+    UnknownDataset { id: String },
+    UnknownAttribute { name: String },
+    UnknownJob { id: u64 },
+    NoActiveDataset,
+    NotTemporal,
+    InvalidArgument { message: String },
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { message } => write!(f, "I/O error: {}", message),
+            Error::MalformedLine { line, content } => {
+                write!(f, "malformed line {}: {:?}", line, content)
+            }
+            Error::InvalidFormat { message } => write!(f, "invalid file: {}", message),
+            Error::EmptyGraph => write!(f, "the graph is empty"),
+            Error::UnknownNode { node } => write!(f, "unknown node {}", node),
+            Error::UnknownLabel { label } => write!(f, "no node is labelled {:?}", label),
+            Error::UnknownDataset { id } => write!(f, "unknown dataset {:?}", id),
+            Error::UnknownAttribute { name } => write!(f, "unknown node attribute {:?}", name),
+            Error::UnknownJob { id } => write!(f, "unknown job {}", id),
+            Error::NoActiveDataset => write!(f, "no dataset is loaded"),
+            Error::NotTemporal => write!(f, "the dataset has no edge timestamps"),
+            Error::InvalidArgument { message } => write!(f, "invalid argument: {}", message),
+            Error::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io {
+            message: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! Sampling estimates of metrics that are too slow to compute exactly on
+//! very large graphs, each with a confidence interval.
+//!
+//! Samples are drawn until the interval is as narrow as requested or the
+//! time budget runs out, whichever comes first. The same seed and budget
+//! give the same estimate, unless the time budget cuts the sampling short.
+
+use crate::error::Error;
+use crate::graph::{intersection_count, Graph};
+use crate::triangles;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimator {
+    /// Mean local clustering coefficient, from one random pair of
+    /// neighbours of each sampled node.
+    AvgClustering,
+    /// Transitivity, from uniformly sampled paths of two edges.
+    Transitivity,
+    /// Mean number of common neighbours over ordered pairs of nodes, from
+    /// sampled pairs.
+    AvgCommonNeighbours,
+    /// Mean number of hops between two nodes over the pairs joined by a
+    /// path, from breadth-first searches out of sampled sources. Edge
+    /// weights are ignored.
+    AvgPathLength,
+}
+
+/// When to stop sampling. Every limit applies; the first one reached ends
+/// the estimate.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Budget {
+    /// Largest acceptable distance from the estimate to either end of the
+    /// interval, 0.01 by default.
+    pub error: Option<f64>,
+    /// Probability that the interval holds the exact value, 0.95 by default.
+    pub confidence: Option<f64>,
+    /// Longest time to sample for, 10 seconds by default.
+    pub time_limit_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+    pub confidence: f64,
+    pub samples: usize,
+    /// Whether the interval is as narrow as requested, as opposed to cut
+    /// short by the time budget.
+    pub converged: bool,
+}
+
+/// Samples drawn before the interval may end the sampling.
+const MIN_SAMPLES: usize = 100;
+/// Samples drawn between checks of the stopping rule.
+const BATCH: usize = 1024;
+/// Breadth-first searches between checks of the stopping rule. They run in
+/// parallel, but the batch does not follow the number of threads, so the
+/// same seed gives the same estimate on every machine.
+const SEARCH_BATCH: usize = 32;
+/// Most samples drawn, for graphs small enough that the budget allows more.
+const MAX_SAMPLES: usize = 100_000_000;
+
+/// Estimates `estimator` on `graph`, drawing random numbers from `seed`.
+pub fn estimate<G: Graph>(
+    graph: &G,
+    estimator: Estimator,
+    budget: &Budget,
+    seed: u64,
+) -> Result<Estimate, Error> {
+    let node_count = graph.node_count();
+    if node_count == 0 {
+        return Err(Error::EmptyGraph);
+    }
+    let sampler = Sampler::new(budget, seed)?;
+    match estimator {
+        Estimator::AvgClustering => sampler.run(Interval::Wilson, BATCH, |rng| {
+            let node = rng.gen_range(0..node_count);
+            (closed_wedge(graph, node, rng) as u8 as f64, 1.0)
+        }),
+        Estimator::Transitivity => {
+            // cumulative number of paths of two edges centred on each node
+            let mut wedges = Vec::with_capacity(node_count);
+            let mut total = 0u64;
+            for node in graph.nodes() {
+                let degree = triangles::simple_degree(graph, node) as u64;
+                total += degree * degree.saturating_sub(1) / 2;
+                wedges.push(total);
+            }
+            if total == 0 {
+                return Ok(sampler.exact(0.0));
+            }
+            sampler.run(Interval::Wilson, BATCH, |rng| {
+                let wedge = rng.gen_range(0..total);
+                let node = wedges.partition_point(|&end| end <= wedge);
+                (closed_wedge(graph, node, rng) as u8 as f64, 1.0)
+            })
+        }
+        Estimator::AvgCommonNeighbours => sampler.run(Interval::Normal, BATCH, |rng| {
+            let first = rng.gen_range(0..node_count);
+            let second = rng.gen_range(0..node_count);
+            let common = intersection_count(graph.neighbors(first), graph.neighbors(second));
+            (common as f64, 1.0)
+        }),
+        Estimator::AvgPathLength => {
+            let mut sampler = sampler;
+            let mut sources = Vec::new();
+            sampler.run_batches(Interval::Normal, SEARCH_BATCH, |rng| {
+                sources.clear();
+                sources.extend((0..SEARCH_BATCH).map(|_| rng.gen_range(0..node_count)));
+                sources
+                    .par_iter()
+                    .map(|&source| hops_from(graph, source))
+                    .collect()
+            })
+        }
+    }
+}
+
+/// Whether a random pair of neighbours of `node` is linked, either way.
+/// Nodes with fewer than two neighbours have none.
+fn closed_wedge<G: Graph>(graph: &G, node: usize, rng: &mut StdRng) -> bool {
+    let degree = triangles::simple_degree(graph, node);
+    if degree < 2 {
+        return false;
+    }
+    let first = rng.gen_range(0..degree);
+    let mut second = rng.gen_range(0..degree - 1);
+    if second >= first {
+        second += 1;
+    }
+    let (a, b) = (
+        other_neighbor(graph, node, first),
+        other_neighbor(graph, node, second),
+    );
+    graph.has_edge(a, b) || graph.has_edge(b, a)
+}
+
+/// The neighbour at `index` among the neighbours of `node` other than itself.
+fn other_neighbor<G: Graph>(graph: &G, node: usize, index: usize) -> usize {
+    let neighbors = graph.neighbors(node);
+    match neighbors.binary_search(&(node as u32)) {
+        Ok(own) if index >= own => neighbors[index + 1] as usize,
+        _ => neighbors[index] as usize,
+    }
+}
+
+/// Sum of the hops from `source` to every other node it reaches, and the
+/// number of those nodes.
+fn hops_from<G: Graph>(graph: &G, source: usize) -> (f64, f64) {
+    let mut distances = vec![u32::MAX; graph.node_count()];
+    distances[source] = 0;
+    let mut queue = VecDeque::from([source]);
+    let (mut total, mut reached) = (0u64, 0u64);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[node] + 1;
+        for &neighbor in graph.neighbors(node) {
+            let neighbor = neighbor as usize;
+            if distances[neighbor] == u32::MAX {
+                distances[neighbor] = distance;
+                total += distance as u64;
+                reached += 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    (total as f64, reached as f64)
+}
+
+#[derive(Clone, Copy)]
+enum Interval {
+    /// Wilson score interval, for samples that are 0 or 1. Stays honest when
+    /// every sample so far had the same value.
+    Wilson,
+    /// Normal approximation of a ratio of sums.
+    Normal,
+}
+
+/// Running sums of `(y, x)` samples, estimating `sum(y) / sum(x)`.
+#[derive(Default)]
+struct Tally {
+    samples: usize,
+    y: f64,
+    x: f64,
+    yy: f64,
+    xx: f64,
+    xy: f64,
+}
+
+impl Tally {
+    fn add(&mut self, (y, x): (f64, f64)) {
+        self.samples += 1;
+        self.y += y;
+        self.x += x;
+        self.yy += y * y;
+        self.xx += x * x;
+        self.xy += x * y;
+    }
+
+    /// The estimate and its interval for the normal quantile `z` of
+    /// `confidence`.
+    fn interval(&self, interval: Interval, z: f64, confidence: f64) -> (f64, f64, f64) {
+        if self.x == 0.0 {
+            return (0.0, 0.0, f64::INFINITY);
+        }
+        let value = self.y / self.x;
+        let n = self.samples as f64;
+        match interval {
+            Interval::Wilson => {
+                let z2 = z * z;
+                let centre = (value + z2 / (2.0 * n)) / (1.0 + z2 / n);
+                let half =
+                    z / (1.0 + z2 / n) * (value * (1.0 - value) / n + z2 / (4.0 * n * n)).sqrt();
+                (value, (centre - half).max(0.0), (centre + half).min(1.0))
+            }
+            Interval::Normal => {
+                if self.samples < 2 {
+                    return (value, f64::NEG_INFINITY, f64::INFINITY);
+                }
+                // variance of the residuals y - value * x, by the delta method
+                let residuals =
+                    (self.yy - 2.0 * value * self.xy + value * value * self.xx).max(0.0);
+                if residuals == 0.0 {
+                    // every sample agreed so far, which on a sparse graph means
+                    // the rare non-zero ones were all missed: fall back on the
+                    // rule of three, a rate of -ln(1 - confidence) / n
+                    let half = -(1.0 - confidence).ln() / n;
+                    return (value, (value - half).max(0.0), value + half);
+                }
+                let mean_x = self.x / n;
+                let half = z * (residuals / (n - 1.0) / n).sqrt() / mean_x;
+                (value, (value - half).max(0.0), value + half)
+            }
+        }
+    }
+}
+
+struct Sampler {
+    rng: StdRng,
+    error: f64,
+    confidence: f64,
+    z: f64,
+    deadline: Instant,
+}
+
+impl Sampler {
+    fn new(budget: &Budget, seed: u64) -> Result<Self, Error> {
+        let error = budget.error.unwrap_or(0.01);
+        let confidence = budget.confidence.unwrap_or(0.95);
+        if !(error > 0.0 && error.is_finite()) {
+            return Err(Error::InvalidArgument {
+                message: format!("error {} is not a positive number", error),
+            });
+        }
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return Err(Error::InvalidArgument {
+                message: format!("confidence {} is not between 0 and 1", confidence),
+            });
+        }
+        let time_limit = Duration::from_millis(budget.time_limit_ms.unwrap_or(10_000));
+        Ok(Sampler {
+            rng: StdRng::seed_from_u64(seed),
+            error,
+            confidence,
+            z: normal_quantile(0.5 + confidence / 2.0),
+            deadline: Instant::now() + time_limit,
+        })
+    }
+
+    /// An estimate that needs no sampling.
+    fn exact(&self, value: f64) -> Estimate {
+        Estimate {
+            value,
+            low: value,
+            high: value,
+            confidence: self.confidence,
+            samples: 0,
+            converged: true,
+        }
+    }
+
+    /// Samples with `draw`, `batch` samples between checks of the budget.
+    fn run<F>(mut self, interval: Interval, batch: usize, mut draw: F) -> Result<Estimate, Error>
+    where
+        F: FnMut(&mut StdRng) -> (f64, f64),
+    {
+        self.run_batches(interval, batch, |rng| {
+            (0..batch).map(|_| draw(rng)).collect()
+        })
+    }
+
+    /// Samples with `draw`, which returns a batch of samples at a time.
+    fn run_batches<F>(
+        &mut self,
+        interval: Interval,
+        batch: usize,
+        mut draw: F,
+    ) -> Result<Estimate, Error>
+    where
+        F: FnMut(&mut StdRng) -> Vec<(f64, f64)>,
+    {
+        let mut tally = Tally::default();
+        loop {
+            for sample in draw(&mut self.rng) {
+                tally.add(sample);
+            }
+            let (value, low, high) = tally.interval(interval, self.z, self.confidence);
+            let converged = tally.samples >= MIN_SAMPLES
+                && value - low <= self.error
+                && high - value <= self.error;
+            if converged || Instant::now() >= self.deadline || tally.samples + batch > MAX_SAMPLES {
+                return Ok(Estimate {
+                    value,
+                    low,
+                    high,
+                    confidence: self.confidence,
+                    samples: tally.samples,
+                    converged,
+                });
+            }
+        }
+    }
+}
+
+/// The `p` quantile of the standard normal distribution, by Acklam's
+/// rational approximation, which is accurate to about 1e-9.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+    use crate::functions;
+    use crate::generators::{generate, Generator};
+
+    fn graph() -> Csr {
+        let model = Generator::WattsStrogatz {
+            nodes: 300,
+            neighbors: 6,
+            rewiring: 0.1,
+        };
+        generate(&model, 3).unwrap().graph
+    }
+
+    fn budget(error: f64) -> Budget {
+        Budget {
+            error: Some(error),
+            confidence: Some(0.999),
+            time_limit_ms: Some(60_000),
+        }
+    }
+
+    fn assert_covers(estimate: &Estimate, exact: f64) {
+        assert!(estimate.converged);
+        assert!(
+            estimate.low <= exact && exact <= estimate.high,
+            "{:?} misses {}",
+            estimate,
+            exact
+        );
+    }
+
+    #[test]
+    fn intervals_cover_the_exact_values() {
+        let graph = graph();
+        let clustering = estimate(&graph, Estimator::AvgClustering, &budget(0.02), 1).unwrap();
+        assert_covers(&clustering, functions::get_avg_cl_coef(&graph).unwrap());
+        let transitivity = estimate(&graph, Estimator::Transitivity, &budget(0.02), 2).unwrap();
+        assert_covers(&transitivity, functions::get_transitivity(&graph).unwrap());
+        let common = estimate(&graph, Estimator::AvgCommonNeighbours, &budget(0.01), 3).unwrap();
+        assert_covers(&common, functions::get_avg_cm_nb(&graph).unwrap());
+    }
+
+    #[test]
+    fn path_length() {
+        let graph = graph();
+        let (total, pairs) = graph
+            .nodes()
+            .map(|source| hops_from(&graph, source))
+            .fold((0.0, 0.0), |(t, p), (total, pairs)| (t + total, p + pairs));
+        let estimate = estimate(&graph, Estimator::AvgPathLength, &budget(0.1), 4).unwrap();
+        assert_covers(&estimate, total / pairs);
+    }
+
+    #[test]
+    fn sparse_graph() {
+        // one triangle and a few tails among 20000 nodes: almost every
+        // sampled pair has no common neighbour
+        let mut edges = vec![(0, 1), (1, 2), (2, 0)];
+        edges.extend((3..40).map(|node| (node, node + 1)));
+        let graph = Csr::unweighted(20_000, &edges);
+        let exact = functions::get_avg_cm_nb(&graph).unwrap();
+        let budget = Budget {
+            confidence: Some(0.95),
+            ..budget(0.001)
+        };
+        let common = estimate(&graph, Estimator::AvgCommonNeighbours, &budget, 5).unwrap();
+        assert_covers(&common, exact);
+        assert!(common.high > 0.0);
+        // 3 / n <= 0.001 takes at least 3000 samples
+        assert!(common.samples >= 2996, "{:?}", common);
+    }
+
+    #[test]
+    fn same_seed_same_estimate() {
+        let graph = graph();
+        let a = estimate(&graph, Estimator::Transitivity, &budget(0.05), 9).unwrap();
+        let b = estimate(&graph, Estimator::Transitivity, &budget(0.05), 9).unwrap();
+        assert_eq!((a.value, a.samples), (b.value, b.samples));
+
+        // the number of threads does not change the path length estimate
+        let paths = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| estimate(&graph, Estimator::AvgPathLength, &budget(0.1), 9))
+                .unwrap()
+        };
+        let (one, four) = (paths(1), paths(4));
+        assert_eq!((one.value, one.samples), (four.value, four.samples));
+    }
+
+    #[test]
+    fn rejects_bad_budgets() {
+        let graph = graph();
+        let budget = Budget {
+            confidence: Some(1.0),
+            ..Budget::default()
+        };
+        assert!(estimate(&graph, Estimator::AvgClustering, &budget, 0).is_err());
+        let empty = Csr::unweighted(0, &[]);
+        let result = estimate(&empty, Estimator::AvgClustering, &Budget::default(), 0);
+        assert!(matches!(result, Err(Error::EmptyGraph)));
+    }
+
+    #[test]
+    fn quantiles() {
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+        assert!((normal_quantile(0.0005) + 3.290527).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,521 @@
+use crate::dataset::{Dataset, DegreeMode};
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::path;
+use quick_xml::escape::escape;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    GraphMl,
+    Gexf,
+    Dot,
+    EdgeList,
+}
+
+/// The part of the dataset to export. Node IDs are the IDs from the input file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportScope {
+    #[default]
+    Full,
+    /// The shortest path between two nodes and the edges around it,
+    /// as returned by `djikstra_path`. There must be a path.
+    Path { start: usize, end: usize },
+    /// The given nodes and all their edges, for instance the nodes reached by
+    /// `simulate_influnce_spread`.
+    Nodes { nodes: Vec<usize> },
+}
+
+/// Computed per-node values written next to the graph. Edge lists have no
+/// room for them and ignore them.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeAttribute {
+    Degree,
+    Clustering,
+    Community,
+}
+
+impl NodeAttribute {
+    fn name(self) -> &'static str {
+        match self {
+            NodeAttribute::Degree => "degree",
+            NodeAttribute::Clustering => "clustering",
+            NodeAttribute::Community => "community",
+        }
+    }
+
+    fn is_integer(self) -> bool {
+        self != NodeAttribute::Clustering
+    }
+}
+
+/// What is written: node indices, edges between node indices and, for every
+/// requested attribute, its value per node index.
+struct Selection {
+    nodes: Vec<usize>,
+    edges: Vec<(usize, usize, f64)>,
+    attributes: Vec<(NodeAttribute, Vec<f64>)>,
+}
+
+/// Writes the requested part of `dataset` to `path`.
+pub fn export(
+    dataset: &Dataset,
+    path: &Path,
+    format: ExportFormat,
+    scope: &ExportScope,
+    attributes: &[NodeAttribute],
+) -> Result<(), Error> {
+    let selection = select(dataset, scope, attributes)?;
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        ExportFormat::GraphMl => write_graphml(&mut out, dataset, &selection)?,
+        ExportFormat::Gexf => write_gexf(&mut out, dataset, &selection)?,
+        ExportFormat::Dot => write_dot(&mut out, dataset, &selection)?,
+        ExportFormat::EdgeList => write_edge_list(&mut out, dataset, &selection)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn select(
+    dataset: &Dataset,
+    scope: &ExportScope,
+    attributes: &[NodeAttribute],
+) -> Result<Selection, Error> {
+    let graph = &dataset.graph;
+    let centres: Option<Vec<usize>> = match scope {
+        ExportScope::Full => None,
+        ExportScope::Path { start, end } => {
+            let (from, to) = (dataset.ensure_node(*start)?, dataset.ensure_node(*end)?);
+            Some(
+                path::dijkstra(graph, from, to).ok_or_else(|| Error::InvalidArgument {
+                    message: format!("no path from node {} to node {}", start, end),
+                })?,
+            )
+        }
+        ExportScope::Nodes { nodes } => Some(
+            nodes
+                .iter()
+                .map(|&node| dataset.ensure_node(node))
+                .collect::<Result<_, _>>()?,
+        ),
+    };
+
+    // undirected edges are stored both ways, keep them once
+    let keep = |from: usize, to: usize| dataset.is_directed() || from <= to;
+    let (nodes, edges) = match centres {
+        None => {
+            let edges = graph
+                .nodes()
+                .flat_map(|from| {
+                    graph
+                        .weighted_neighbors(from)
+                        .filter(move |&(to, _)| keep(from, to))
+                        .map(move |(to, weight)| (from, to, weight))
+                })
+                .collect();
+            (graph.nodes().collect(), edges)
+        }
+        Some(centres) => {
+            let mut nodes: BTreeSet<usize> = centres.iter().copied().collect();
+            let mut edges = BTreeSet::new();
+            let mut add = |from: usize, to: usize, weight: f64| {
+                nodes.insert(from);
+                nodes.insert(to);
+                let (from, to) = if keep(from, to) {
+                    (from, to)
+                } else {
+                    (to, from)
+                };
+                edges.insert((from, to, weight.to_bits()));
+            };
+            for &node in &centres {
+                for (neighbor, weight) in graph.weighted_neighbors(node) {
+                    add(node, neighbor, weight);
+                }
+                if let Some(incoming) = &dataset.incoming {
+                    for (neighbor, weight) in incoming.weighted_neighbors(node) {
+                        add(neighbor, node, weight);
+                    }
+                }
+            }
+            let edges = edges
+                .into_iter()
+                .map(|(from, to, weight)| (from, to, f64::from_bits(weight)))
+                .collect();
+            (nodes.into_iter().collect(), edges)
+        }
+    };
+
+    let mut values = Vec::new();
+    if !attributes.is_empty() {
+        let view = dataset.view(DegreeMode::Total);
+        for &attribute in attributes {
+            let per_node = match attribute {
+                NodeAttribute::Degree => {
+                    view.nodes().map(|node| view.degree(node) as f64).collect()
+                }
+                NodeAttribute::Clustering => {
+                    dataset.node_metrics(DegreeMode::Total).clustering.clone()
+                }
+                NodeAttribute::Community => dataset
+                    .communities()
+                    .iter()
+                    .map(|&community| dataset.id(community) as f64)
+                    .collect(),
+            };
+            values.push((attribute, per_node));
+        }
+    }
+
+    Ok(Selection {
+        nodes,
+        edges,
+        attributes: values,
+    })
+}
+
+fn format_value(attribute: NodeAttribute, value: f64) -> String {
+    if attribute.is_integer() {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn write_graphml<W: Write>(
+    out: &mut W,
+    dataset: &Dataset,
+    selection: &Selection,
+) -> Result<(), Error> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="weight" for="edge" attr.name="weight" attr.type="double"/>"#
+    )?;
+    for (attribute, _) in &selection.attributes {
+        let kind = if attribute.is_integer() {
+            "long"
+        } else {
+            "double"
+        };
+        writeln!(
+            out,
+            r#"  <key id="{0}" for="node" attr.name="{0}" attr.type="{1}"/>"#,
+            attribute.name(),
+            kind
+        )?;
+    }
+    let edge_default = if dataset.is_directed() {
+        "directed"
+    } else {
+        "undirected"
+    };
+    writeln!(out, r#"  <graph edgedefault="{}">"#, edge_default)?;
+    for &node in &selection.nodes {
+        let id = dataset.id(node);
+        writeln!(out, r#"    <node id="{}">"#, id)?;
+        if let Some(label) = dataset.labels.label(id) {
+            writeln!(out, r#"      <data key="label">{}</data>"#, escape(label))?;
+        }
+        for (attribute, values) in &selection.attributes {
+            writeln!(
+                out,
+                r#"      <data key="{}">{}</data>"#,
+                attribute.name(),
+                format_value(*attribute, values[node])
+            )?;
+        }
+        writeln!(out, "    </node>")?;
+    }
+    for &(from, to, weight) in &selection.edges {
+        writeln!(
+            out,
+            r#"    <edge source="{}" target="{}"><data key="weight">{}</data></edge>"#,
+            dataset.id(from),
+            dataset.id(to),
+            weight
+        )?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    Ok(())
+}
+
+fn write_gexf<W: Write>(
+    out: &mut W,
+    dataset: &Dataset,
+    selection: &Selection,
+) -> Result<(), Error> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+    let edge_type = if dataset.is_directed() {
+        "directed"
+    } else {
+        "undirected"
+    };
+    writeln!(
+        out,
+        r#"  <graph mode="static" defaultedgetype="{}">"#,
+        edge_type
+    )?;
+    if !selection.attributes.is_empty() {
+        writeln!(out, r#"    <attributes class="node">"#)?;
+        for (attribute, _) in &selection.attributes {
+            let kind = if attribute.is_integer() {
+                "long"
+            } else {
+                "double"
+            };
+            writeln!(
+                out,
+                r#"      <attribute id="{0}" title="{0}" type="{1}"/>"#,
+                attribute.name(),
+                kind
+            )?;
+        }
+        writeln!(out, "    </attributes>")?;
+    }
+    writeln!(out, "    <nodes>")?;
+    for &node in &selection.nodes {
+        let id = dataset.id(node);
+        let label = dataset
+            .labels
+            .label(id)
+            .map_or_else(|| id.to_string(), |label| label.to_string());
+        writeln!(
+            out,
+            r#"      <node id="{}" label="{}">"#,
+            id,
+            escape(&label)
+        )?;
+        if !selection.attributes.is_empty() {
+            writeln!(out, "        <attvalues>")?;
+            for (attribute, values) in &selection.attributes {
+                writeln!(
+                    out,
+                    r#"          <attvalue for="{}" value="{}"/>"#,
+                    attribute.name(),
+                    format_value(*attribute, values[node])
+                )?;
+            }
+            writeln!(out, "        </attvalues>")?;
+        }
+        writeln!(out, "      </node>")?;
+    }
+    writeln!(out, "    </nodes>")?;
+    writeln!(out, "    <edges>")?;
+    for (index, &(from, to, weight)) in selection.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"      <edge id="{}" source="{}" target="{}" weight="{}"/>"#,
+            index,
+            dataset.id(from),
+            dataset.id(to),
+            weight
+        )?;
+    }
+    writeln!(out, "    </edges>")?;
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")?;
+    Ok(())
+}
+
+fn write_dot<W: Write>(out: &mut W, dataset: &Dataset, selection: &Selection) -> Result<(), Error> {
+    let (keyword, arrow) = if dataset.is_directed() {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    writeln!(out, "{} {{", keyword)?;
+    for &node in &selection.nodes {
+        let id = dataset.id(node);
+        let mut attributes = Vec::new();
+        if let Some(label) = dataset.labels.label(id) {
+            attributes.push(format!("label=\"{}\"", dot_escape(label)));
+        }
+        for (attribute, values) in &selection.attributes {
+            attributes.push(format!(
+                "{}={}",
+                attribute.name(),
+                format_value(*attribute, values[node])
+            ));
+        }
+        if attributes.is_empty() {
+            writeln!(out, "  {};", id)?;
+        } else {
+            writeln!(out, "  {} [{}];", id, attributes.join(", "))?;
+        }
+    }
+    for &(from, to, weight) in &selection.edges {
+        writeln!(
+            out,
+            "  {} {} {} [weight={}];",
+            dataset.id(from),
+            arrow,
+            dataset.id(to),
+            weight
+        )?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// One `from to weight` line per edge, readable by `load_dataset` with a weight column of 2.
+fn write_edge_list<W: Write>(
+    out: &mut W,
+    dataset: &Dataset,
+    selection: &Selection,
+) -> Result<(), Error> {
+    for &(from, to, weight) in &selection.edges {
+        writeln!(out, "{} {} {}", dataset.id(from), dataset.id(to), weight)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{DatasetBuilder, GraphMode};
+    use crate::loader::{self, DuplicatePolicy, LoadMonitor, LoadOptions, LoadProgress};
+    use std::sync::atomic::AtomicBool;
+
+    /// A triangle 1-2-3 with a tail 3-4 of weight 2, node 1 labelled "a & b".
+    fn paw() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        for (from, to, weight) in [(1, 2, 1.0), (2, 3, 1.0), (3, 1, 1.0), (3, 4, 2.0)] {
+            builder.insert_edge(from, to, weight);
+        }
+        builder.labels.set_label(1, "a & b".to_string());
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    fn write(dataset: &Dataset, format: ExportFormat, scope: &ExportScope) -> String {
+        let selection = select(dataset, scope, &[NodeAttribute::Degree]).unwrap();
+        let mut out = Vec::new();
+        match format {
+            ExportFormat::GraphMl => write_graphml(&mut out, dataset, &selection),
+            ExportFormat::Gexf => write_gexf(&mut out, dataset, &selection),
+            ExportFormat::Dot => write_dot(&mut out, dataset, &selection),
+            ExportFormat::EdgeList => write_edge_list(&mut out, dataset, &selection),
+        }
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Exports `dataset` to a file and loads it back.
+    fn round_trip(dataset: &Dataset, format: ExportFormat, name: &str) -> Dataset {
+        let path = crate::temp_path(name);
+        export(dataset, &path, format, &ExportScope::Full, &[]).unwrap();
+        let options = LoadOptions {
+            weight_column: Some(2),
+            ..Default::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(0, &cancel, &report);
+        let copy = loader::load_file(&path, &options, &mut monitor).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        copy
+    }
+
+    fn weighted_edges(dataset: &Dataset) -> Vec<(usize, usize, f64)> {
+        let graph = &dataset.graph;
+        let mut edges: Vec<_> = graph
+            .nodes()
+            .flat_map(|from| {
+                graph
+                    .weighted_neighbors(from)
+                    .map(move |(to, weight)| (dataset.id(from), dataset.id(to), weight))
+            })
+            .collect();
+        edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        edges
+    }
+
+    #[test]
+    fn edge_list_round_trip() {
+        let dataset = paw();
+        let copy = round_trip(&dataset, ExportFormat::EdgeList, "export.txt");
+        assert_eq!(weighted_edges(&copy), weighted_edges(&dataset));
+    }
+
+    #[test]
+    fn graphml_round_trip() {
+        let dataset = paw();
+        let copy = round_trip(&dataset, ExportFormat::GraphMl, "export.graphml");
+        // GraphML node IDs are interned in document order, which is ID order here
+        assert_eq!(copy.graph.raw_parts(), dataset.graph.raw_parts());
+        assert_eq!(
+            copy.labels.label(copy.labels.find("1").unwrap()),
+            Some("a & b")
+        );
+    }
+
+    #[test]
+    fn writers() {
+        let dataset = paw();
+        let full = ExportScope::Full;
+        let graphml = write(&dataset, ExportFormat::GraphMl, &full);
+        assert!(graphml.contains(r#"<data key="label">a &amp; b</data>"#));
+        assert!(graphml.contains(r#"<node id="3">"#));
+        assert!(graphml.contains(r#"<data key="degree">3</data>"#));
+
+        let gexf = write(&dataset, ExportFormat::Gexf, &full);
+        assert!(gexf.contains(r#"defaultedgetype="undirected""#));
+        assert!(gexf.contains(r#"<edge id="3" source="3" target="4" weight="2"/>"#));
+
+        let dot = write(&dataset, ExportFormat::Dot, &full);
+        assert!(dot.starts_with("graph {"));
+        assert!(dot.contains("  1 [label=\"a & b\", degree=2];"));
+        assert!(dot.contains("  3 -- 4 [weight=2];"));
+    }
+
+    #[test]
+    fn scopes() {
+        let dataset = paw();
+        // the neighbourhood of the tail 4 is the edge 3-4 alone
+        let nodes = ExportScope::Nodes { nodes: vec![4] };
+        assert_eq!(write(&dataset, ExportFormat::EdgeList, &nodes), "3 4 2\n");
+
+        let path = ExportScope::Path { start: 1, end: 4 };
+        let edges = write(&dataset, ExportFormat::EdgeList, &path);
+        assert_eq!(edges.lines().count(), 4);
+
+        // an unreachable end is an error rather than an empty export
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        builder.insert_edge(1, 2, 1.0);
+        builder.insert_node(5);
+        let apart = builder.build(DuplicatePolicy::First).unwrap().0;
+        let unreachable = ExportScope::Path { start: 1, end: 5 };
+        assert!(matches!(
+            select(&apart, &unreachable, &[]),
+            Err(Error::InvalidArgument { .. })
+        ));
+
+        let unknown = ExportScope::Nodes { nodes: vec![9] };
+        assert!(matches!(
+            select(&dataset, &unknown, &[]),
+            Err(Error::UnknownNode { .. })
+        ));
+    }
+}
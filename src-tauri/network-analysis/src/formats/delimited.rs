@@ -0,0 +1,158 @@
+use super::ImportedGraph;
+use crate::error::Error;
+use crate::loader::LoadMonitor;
+use serde::Deserialize;
+use std::io::Read;
+
+/// A CSV column, either by zero based position or by header name.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub has_header: bool,
+    pub source: Column,
+    pub target: Column,
+    pub weight: Option<Column>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            has_header: true,
+            source: Column::Index(0),
+            target: Column::Index(1),
+            weight: None,
+        }
+    }
+}
+
+/// Reads a delimited edge list. Node identifiers are arbitrary strings that are
+/// interned in order of appearance and kept as the node labels.
+/// Rows with missing columns or an invalid weight are skipped unless `strict` is set.
+pub fn read<R: Read>(
+    reader: R,
+    options: &CsvOptions,
+    directed: bool,
+    strict: bool,
+    monitor: &mut LoadMonitor,
+) -> Result<ImportedGraph, Error> {
+    if !options.delimiter.is_ascii() {
+        return Err(Error::InvalidFormat {
+            message: format!(
+                "delimiter {:?} is not an ASCII character",
+                options.delimiter
+            ),
+        });
+    }
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter as u8)
+        .has_headers(options.has_header)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+
+    let headers = if options.has_header {
+        Some(reader.headers().map_err(invalid)?.clone())
+    } else {
+        None
+    };
+    let resolve = |column: &Column| -> Result<usize, Error> {
+        match column {
+            Column::Index(index) => Ok(*index),
+            Column::Name(name) => headers
+                .as_ref()
+                .and_then(|headers| headers.iter().position(|h| h == name))
+                .ok_or_else(|| Error::InvalidFormat {
+                    message: format!("no column named {:?}", name),
+                }),
+        }
+    };
+    let source = resolve(&options.source)?;
+    let target = resolve(&options.target)?;
+    let weight = options.weight.as_ref().map(resolve).transpose()?;
+
+    let separator = options.delimiter.to_string();
+    let mut graph = ImportedGraph {
+        directed,
+        ..Default::default()
+    };
+    for record in reader.records() {
+        let record = record.map_err(invalid)?;
+        match parse_record(&record, source, target, weight) {
+            Some((from, to, weight)) => {
+                let from = graph.labels.intern(from);
+                let to = graph.labels.intern(to);
+                graph.push(from, to, weight, directed);
+                monitor.parsed()?;
+            }
+            None => {
+                let line = record.position().map_or(0, |p| p.line() as usize);
+                let content = record.iter().collect::<Vec<_>>().join(&separator);
+                if strict {
+                    return Err(Error::MalformedLine { line, content });
+                }
+                monitor.skipped(line, &content)?;
+            }
+        }
+    }
+    Ok(graph)
+}
+
+fn parse_record(
+    record: &::csv::StringRecord,
+    source: usize,
+    target: usize,
+    weight: Option<usize>,
+) -> Option<(&str, &str, f64)> {
+    let from = record.get(source).filter(|s| !s.is_empty())?;
+    let to = record.get(target).filter(|s| !s.is_empty())?;
+    let weight = match weight {
+        Some(column) => record.get(column)?.parse::<f64>().ok()?,
+        None => 1.0,
+    };
+    if !weight.is_finite() || weight < 0.0 {
+        return None;
+    }
+    Some((from, to, weight))
+}
+
+fn invalid(error: ::csv::Error) -> Error {
+    Error::InvalidFormat {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::LoadProgress;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn reports_rows_with_their_delimiter() {
+        let options = CsvOptions {
+            delimiter: ';',
+            weight: Some(Column::Name("w".to_string())),
+            ..Default::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(0, &cancel, &report);
+        let content = "from;to;w\na;b;1\nb;c;heavy\n";
+        match read(content.as_bytes(), &options, false, true, &mut monitor) {
+            Err(Error::MalformedLine { line, content }) => {
+                assert_eq!(line, 3);
+                assert_eq!(content, "b;c;heavy");
+            }
+            _ => panic!("invalid weight accepted"),
+        }
+    }
+}
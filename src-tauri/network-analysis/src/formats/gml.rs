@@ -0,0 +1,240 @@
+use super::ImportedGraph;
+use crate::error::Error;
+use crate::loader::LoadMonitor;
+use std::iter::Peekable;
+use std::str::Chars;
+
+enum Value {
+    /// A number or string, with the line it is on.
+    Scalar(String, usize),
+    List(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::List(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            Value::Scalar(..) => None,
+        }
+    }
+
+    fn scalar(&self, key: &str) -> Option<&str> {
+        self.scalar_at(key).map(|(value, _)| value)
+    }
+
+    fn scalar_at(&self, key: &str) -> Option<(&str, usize)> {
+        match self.get(key) {
+            Some(Value::Scalar(value, line)) => Some((value, *line)),
+            _ => None,
+        }
+    }
+}
+
+/// Reads a GML document. Node IDs are kept, labels come from the `label`
+/// key and edge weights from `weight` or `value`. A weight that is negative
+/// or not finite is reported with its line.
+pub fn read(content: &str, monitor: &mut LoadMonitor) -> Result<ImportedGraph, Error> {
+    let mut lexer = Lexer {
+        chars: content.chars().peekable(),
+        line: 1,
+    };
+    let document = parse_list(&mut lexer, false, monitor)?;
+    let root = document
+        .get("graph")
+        .ok_or_else(|| invalid("no graph [ ... ] block"))?;
+    let entries = match root {
+        Value::List(entries) => entries,
+        Value::Scalar(..) => return Err(invalid("graph is not a list")),
+    };
+
+    let mut graph = ImportedGraph {
+        directed: root.scalar("directed") == Some("1"),
+        ..Default::default()
+    };
+    for (key, value) in entries {
+        match key.as_str() {
+            "node" => {
+                let node = node_id(value, "id")?;
+                graph.nodes.push(node);
+                if let Some(label) = value.scalar("label") {
+                    graph.labels.set_label(node, label.to_string());
+                }
+            }
+            "edge" => {
+                let source = node_id(value, "source")?;
+                let target = node_id(value, "target")?;
+                let weight = match value
+                    .scalar_at("weight")
+                    .or_else(|| value.scalar_at("value"))
+                {
+                    Some((text, line)) => match text.parse::<f64>() {
+                        Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                        _ => {
+                            return Err(invalid(&format!(
+                                "invalid edge weight {:?} on line {}",
+                                text, line
+                            )))
+                        }
+                    },
+                    None => 1.0,
+                };
+                graph.push(source, target, weight, graph.directed);
+            }
+            _ => {}
+        }
+    }
+    Ok(graph)
+}
+
+fn node_id(value: &Value, key: &str) -> Result<usize, Error> {
+    let id = value
+        .scalar(key)
+        .ok_or_else(|| invalid(&format!("missing {}", key)))?;
+    id.parse()
+        .map_err(|_| invalid(&format!("invalid node id {:?}", id)))
+}
+
+fn parse_list(lexer: &mut Lexer, nested: bool, monitor: &mut LoadMonitor) -> Result<Value, Error> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match lexer.next_token()? {
+            Some(Token::Word(key)) => key,
+            Some(Token::Close) if nested => return Ok(Value::List(entries)),
+            None if !nested => return Ok(Value::List(entries)),
+            _ => return Err(invalid("expected a key")),
+        };
+        let value = match lexer.next_token()? {
+            Some(Token::Word(value)) | Some(Token::Str(value)) => Value::Scalar(value, lexer.line),
+            Some(Token::Open) => parse_list(lexer, true, monitor)?,
+            _ => return Err(invalid(&format!("missing value for {}", key))),
+        };
+        entries.push((key, value));
+        monitor.tick()?;
+    }
+}
+
+enum Token {
+    Word(String),
+    Str(String),
+    Open,
+    Close,
+}
+
+/// Splits a GML document into tokens, counting lines as it goes.
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// Line of the last character read.
+    line: usize,
+}
+
+impl Lexer<'_> {
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.next_char();
+            } else if c == '#' {
+                // comment until the end of the line
+                while let Some(c) = self.next_char() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        let c = match self.next_char() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '[' => Token::Open,
+            ']' => Token::Close,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match self.next_char() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(invalid("unterminated string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            c => {
+                let mut value = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || c == '[' || c == ']' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    self.next_char();
+                }
+                Token::Word(value)
+            }
+        };
+        Ok(Some(token))
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidFormat {
+        message: format!("GML: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::LoadProgress;
+    use std::sync::atomic::AtomicBool;
+
+    fn parse(content: &str) -> Result<ImportedGraph, Error> {
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(0, &cancel, &report);
+        read(content, &mut monitor)
+    }
+
+    #[test]
+    fn nodes_and_edges() {
+        let graph = parse(
+            "# a comment\ngraph [\n  directed 1\n  node [ id 1 label \"one\" ]\n  node [ id 2 ]\n  edge [ source 1 target 2 value 2.5 ]\n]\n",
+        )
+        .unwrap();
+        assert!(graph.directed);
+        assert_eq!(graph.nodes, vec![1, 2]);
+        assert_eq!(graph.labels.label(1), Some("one"));
+        assert_eq!(graph.arcs, vec![(1, 2, 2.5)]);
+    }
+
+    #[test]
+    fn rejects_bad_weights() {
+        for weight in ["-1", "NaN", "heavy"] {
+            let content = format!(
+                "graph [\n  edge [ source 1 target 2 ]\n  edge [\n    source 2 target 3\n    weight {}\n  ]\n]\n",
+                weight
+            );
+            match parse(&content) {
+                Err(Error::InvalidFormat { message }) => {
+                    assert!(message.ends_with("on line 5"), "{}", message)
+                }
+                _ => panic!("weight {} accepted", weight),
+            }
+        }
+    }
+
+    #[test]
+    fn unterminated() {
+        assert!(parse("graph [ node [ id 1 ]").is_err());
+        assert!(parse("graph [ node [ label \"one ] ]").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use super::ImportedGraph;
+use crate::error::Error;
+use crate::loader::LoadMonitor;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use rayon_hash::HashMap;
+use std::io::BufRead;
+
+/// Reads a GraphML document. Node IDs are interned in document order, node
+/// labels come from a node key named `label` and edge weights from an edge
+/// key named `weight`. A weight that is negative or not finite is reported
+/// with its line.
+pub fn read<R: BufRead>(reader: R, monitor: &mut LoadMonitor) -> Result<ImportedGraph, Error> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut graph = ImportedGraph::default();
+
+    // key id -> (domain, attribute name)
+    let mut keys: HashMap<String, (String, String)> = HashMap::new();
+    let mut edge_default_directed = false;
+    let mut current_node: Option<usize> = None;
+    let mut current_edge: Option<(usize, usize, f64, bool)> = None;
+    let mut current_data: Option<String> = None;
+    let mut text = String::new();
+    // line of the start of the current event, and of the current <data>
+    let mut line = 1;
+    let mut data_line = 1;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| invalid(&reader, e))?;
+        // the delimiters of an event never hold a line break
+        let breaks = event.iter().filter(|&&byte| byte == b'\n').count();
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"key" => {
+                        let id = attribute(e, b"id")?.unwrap_or_default();
+                        let domain = attribute(e, b"for")?.unwrap_or_default();
+                        let name = attribute(e, b"attr.name")?.unwrap_or_default();
+                        keys.insert(id, (domain, name));
+                    }
+                    b"graph" => {
+                        edge_default_directed =
+                            attribute(e, b"edgedefault")?.as_deref() == Some("directed");
+                    }
+                    b"node" => {
+                        let id = required(e, b"id")?;
+                        let node = graph.labels.intern(&id);
+                        graph.nodes.push(node);
+                        if !is_empty {
+                            current_node = Some(node);
+                        }
+                    }
+                    b"edge" => {
+                        let source = graph.labels.intern(&required(e, b"source")?);
+                        let target = graph.labels.intern(&required(e, b"target")?);
+                        let directed = match attribute(e, b"directed")?.as_deref() {
+                            Some(value) => value == "true",
+                            None => edge_default_directed,
+                        };
+                        if is_empty {
+                            graph.push(source, target, 1.0, directed);
+                        } else {
+                            current_edge = Some((source, target, 1.0, directed));
+                        }
+                    }
+                    b"data" if !is_empty => {
+                        current_data = attribute(e, b"key")?;
+                        data_line = line;
+                        text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(e) if current_data.is_some() => {
+                text.push_str(&e.unescape().map_err(|e| invalid(&reader, e))?);
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"data" => {
+                    let key = current_data.take().unwrap_or_default();
+                    let (domain, name) = keys.get(&key).cloned().unwrap_or_default();
+                    let value = text.trim();
+                    if let (Some(node), "label") = (current_node, name.as_str()) {
+                        if domain != "edge" {
+                            graph.labels.set_label(node, value.to_string());
+                        }
+                    }
+                    if let (Some(edge), "weight") = (current_edge.as_mut(), name.as_str()) {
+                        if domain != "node" {
+                            edge.2 = match value.parse::<f64>() {
+                                Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                                _ => {
+                                    return Err(Error::InvalidFormat {
+                                        message: format!(
+                                            "invalid edge weight {:?} on line {}",
+                                            value, data_line
+                                        ),
+                                    })
+                                }
+                            };
+                        }
+                    }
+                }
+                b"node" => current_node = None,
+                b"edge" => {
+                    if let Some((source, target, weight, directed)) = current_edge.take() {
+                        graph.push(source, target, weight, directed);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        line += breaks;
+        buf.clear();
+        monitor.tick()?;
+    }
+
+    graph.directed = edge_default_directed;
+    Ok(graph)
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, Error> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| Error::InvalidFormat {
+            message: e.to_string(),
+        })?;
+        if attribute.key.as_ref() == name {
+            let value = attribute
+                .unescape_value()
+                .map_err(|e| Error::InvalidFormat {
+                    message: e.to_string(),
+                })?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required(element: &BytesStart, name: &[u8]) -> Result<String, Error> {
+    attribute(element, name)?.ok_or_else(|| Error::InvalidFormat {
+        message: format!(
+            "<{}> without a {} attribute",
+            String::from_utf8_lossy(element.local_name().as_ref()),
+            String::from_utf8_lossy(name)
+        ),
+    })
+}
+
+fn invalid<R>(reader: &Reader<R>, error: quick_xml::Error) -> Error {
+    Error::InvalidFormat {
+        message: format!("{} at byte {}", error, reader.buffer_position()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::LoadProgress;
+    use std::sync::atomic::AtomicBool;
+
+    const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+  <graph edgedefault="undirected">
+"#;
+
+    fn parse(body: &str) -> Result<ImportedGraph, Error> {
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(0, &cancel, &report);
+        let content = format!("{}{}  </graph>\n</graphml>\n", HEADER, body);
+        read(content.as_bytes(), &mut monitor)
+    }
+
+    #[test]
+    fn nodes_and_edges() {
+        let graph = parse(
+            r#"    <node id="a"><data key="d0">Alice</data></node>
+    <node id="b"/>
+    <edge source="a" target="b"><data key="d1">0.5</data></edge>
+    <edge source="b" target="c" directed="true"/>
+"#,
+        )
+        .unwrap();
+        assert!(!graph.directed);
+        assert_eq!(graph.nodes, vec![0, 1]);
+        assert_eq!(graph.labels.label(0), Some("Alice"));
+        assert_eq!(graph.edges, vec![(0, 1, 0.5)]);
+        assert_eq!(graph.arcs, vec![(1, 2, 1.0)]);
+    }
+
+    #[test]
+    fn rejects_bad_weights() {
+        for weight in ["-1", "NaN", "heavy"] {
+            let body = format!(
+                "    <edge source=\"a\" target=\"b\"/>\n    <edge source=\"b\" target=\"c\">\n      <data key=\"d1\">{}</data>\n    </edge>\n",
+                weight
+            );
+            match parse(&body) {
+                Err(Error::InvalidFormat { message }) => {
+                    assert!(message.ends_with("on line 8"), "{}", message)
+                }
+                _ => panic!("weight {} accepted", weight),
+            }
+        }
+    }
+
+    #[test]
+    fn edges_need_endpoints() {
+        assert!(matches!(
+            parse("    <edge source=\"a\"/>\n"),
+            Err(Error::InvalidFormat { .. })
+        ));
+    }
+}
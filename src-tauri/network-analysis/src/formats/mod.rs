@@ -0,0 +1,99 @@
+pub mod delimited;
+pub mod gml;
+pub mod graphml;
+pub mod pajek;
+
+use crate::dataset::{DatasetBuilder, GraphMode};
+use crate::labels::NodeLabels;
+use crate::loader::{LoadMonitor, LoadOptions};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    EdgeList,
+    Csv,
+    GraphMl,
+    Gml,
+    Pajek,
+}
+
+impl Format {
+    /// Picks the format from the file extension, falling back to the first
+    /// non-blank line of the file.
+    pub fn detect(path: &Path, first_line: &str) -> Format {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "csv" => return Format::Csv,
+            "graphml" => return Format::GraphMl,
+            "gml" => return Format::Gml,
+            "net" | "paj" => return Format::Pajek,
+            _ => {}
+        }
+        let first_line = first_line.trim_start().to_lowercase();
+        if first_line.starts_with("<?xml") || first_line.starts_with("<graphml") {
+            Format::GraphMl
+        } else if first_line.starts_with("*network") || first_line.starts_with("*vertices") {
+            Format::Pajek
+        } else if first_line.starts_with("graph") || first_line.starts_with("creator") {
+            Format::Gml
+        } else {
+            Format::EdgeList
+        }
+    }
+}
+
+/// A graph read from one of the structured formats, before it is turned into a dataset.
+#[derive(Default)]
+pub struct ImportedGraph {
+    /// Whether the file declares the graph as directed.
+    pub directed: bool,
+    pub nodes: Vec<usize>,
+    /// Edges declared as directed.
+    pub arcs: Vec<(usize, usize, f64)>,
+    /// Edges declared as undirected.
+    pub edges: Vec<(usize, usize, f64)>,
+    pub labels: NodeLabels,
+}
+
+impl ImportedGraph {
+    pub fn push(&mut self, from: usize, to: usize, weight: f64, directed: bool) {
+        if directed {
+            self.arcs.push((from, to, weight));
+        } else {
+            self.edges.push((from, to, weight));
+        }
+    }
+
+    /// Prepares the dataset. `directed` overrides the direction declared by the file;
+    /// a directed dataset stores undirected edges as a pair of arcs.
+    pub fn into_builder(self, options: &LoadOptions, monitor: &mut LoadMonitor) -> DatasetBuilder {
+        let directed = options
+            .directed
+            .unwrap_or(self.directed || !self.arcs.is_empty());
+        let mode = if directed {
+            GraphMode::Directed
+        } else {
+            GraphMode::Undirected
+        };
+        let mut builder = DatasetBuilder::new(mode);
+        for node in self.nodes {
+            builder.insert_node(node);
+        }
+        for (from, to, weight) in self.arcs {
+            monitor.insert_edge(&mut builder, from, to, weight, None, options);
+        }
+        for (from, to, weight) in self.edges {
+            monitor.insert_edge(&mut builder, from, to, weight, None, options);
+            if directed && from != to {
+                builder.insert_edge(to, from, weight);
+            }
+        }
+        builder.labels = self.labels;
+        builder
+    }
+}
@@ -0,0 +1,171 @@
+use super::ImportedGraph;
+use crate::error::Error;
+use crate::loader::LoadMonitor;
+use std::io::BufRead;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    None,
+    Vertices,
+    Arcs,
+    Edges,
+    ArcsList,
+    EdgesList,
+}
+
+/// Reads a Pajek `.net` file. Vertex numbers are kept as node IDs and quoted
+/// vertex names become labels. `*Arcs` sections make the graph directed.
+/// Unparseable lines, and edges whose weight is negative or not finite, are
+/// skipped unless `strict` is set.
+pub fn read<R: BufRead>(
+    reader: R,
+    strict: bool,
+    monitor: &mut LoadMonitor,
+) -> Result<ImportedGraph, Error> {
+    let mut graph = ImportedGraph::default();
+    let mut section = Section::None;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('%') {
+            monitor.ignored()?;
+            continue;
+        }
+        if trimmed.starts_with('*') {
+            let keyword = trimmed.split_whitespace().next().unwrap_or_default();
+            section = match keyword.to_lowercase().as_str() {
+                "*vertices" => Section::Vertices,
+                "*arcs" => Section::Arcs,
+                "*edges" => Section::Edges,
+                "*arcslist" => Section::ArcsList,
+                "*edgeslist" => Section::EdgesList,
+                _ => Section::None,
+            };
+            if matches!(section, Section::Arcs | Section::ArcsList) {
+                graph.directed = true;
+            }
+            monitor.ignored()?;
+            continue;
+        }
+
+        let parsed = match section {
+            Section::Vertices => parse_vertex(trimmed, &mut graph),
+            Section::Arcs | Section::Edges => {
+                parse_edge(trimmed, section == Section::Arcs, &mut graph)
+            }
+            Section::ArcsList | Section::EdgesList => {
+                parse_list(trimmed, section == Section::ArcsList, &mut graph)
+            }
+            Section::None => Some(()),
+        };
+        match parsed {
+            Some(()) => monitor.parsed()?,
+            None if strict => {
+                return Err(Error::MalformedLine {
+                    line: index + 1,
+                    content: line,
+                })
+            }
+            None => monitor.skipped(index + 1, &line)?,
+        }
+    }
+    Ok(graph)
+}
+
+fn parse_vertex(line: &str, graph: &mut ImportedGraph) -> Option<()> {
+    let (id, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let node = id.parse::<usize>().ok()?;
+    graph.nodes.push(node);
+    let rest = rest.trim_start();
+    let label = if let Some(quoted) = rest.strip_prefix('"') {
+        quoted.split('"').next()
+    } else {
+        rest.split_whitespace().next()
+    };
+    if let Some(label) = label {
+        graph.labels.set_label(node, label.to_string());
+    }
+    Some(())
+}
+
+fn parse_edge(line: &str, directed: bool, graph: &mut ImportedGraph) -> Option<()> {
+    let mut columns = line.split_whitespace();
+    let from = columns.next()?.parse::<usize>().ok()?;
+    let to = columns.next()?.parse::<usize>().ok()?;
+    let weight = match columns.next() {
+        Some(weight) => weight.parse::<f64>().ok()?,
+        None => 1.0,
+    };
+    if !weight.is_finite() || weight < 0.0 {
+        return None;
+    }
+    graph.push(from, to, weight, directed);
+    Some(())
+}
+
+/// Adds the edges of a list line only when all its targets parse, so that a
+/// skipped line adds nothing.
+fn parse_list(line: &str, directed: bool, graph: &mut ImportedGraph) -> Option<()> {
+    let mut columns = line.split_whitespace();
+    let from = columns.next()?.parse::<usize>().ok()?;
+    let targets = columns
+        .map(|to| to.parse::<usize>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    for to in targets {
+        graph.push(from, to, 1.0, directed);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::LoadProgress;
+    use std::sync::atomic::AtomicBool;
+
+    fn parse(content: &str, strict: bool) -> Result<ImportedGraph, Error> {
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(0, &cancel, &report);
+        read(content.as_bytes(), strict, &mut monitor)
+    }
+
+    #[test]
+    fn vertices_and_edges() {
+        let graph = parse(
+            "*Vertices 3\n1 \"first node\"\n2 b\n3\n*Arcs\n1 2 0.5\n*Edges\n2 3\n*Edgeslist\n3 1 2\n",
+            true,
+        )
+        .unwrap();
+        assert!(graph.directed);
+        assert_eq!(graph.nodes, vec![1, 2, 3]);
+        assert_eq!(graph.labels.label(1), Some("first node"));
+        assert_eq!(graph.arcs, vec![(1, 2, 0.5)]);
+        assert_eq!(graph.edges, vec![(2, 3, 1.0), (3, 1, 1.0), (3, 2, 1.0)]);
+    }
+
+    #[test]
+    fn rejects_bad_weights() {
+        for weight in ["-1", "NaN", "inf"] {
+            let content = format!("*Edges\n1 2\n1 3 {}\n", weight);
+            match parse(&content, true) {
+                Err(Error::MalformedLine { line, .. }) => assert_eq!(line, 3),
+                _ => panic!("weight {} accepted", weight),
+            }
+            assert_eq!(parse(&content, false).unwrap().edges, vec![(1, 2, 1.0)]);
+        }
+    }
+
+    #[test]
+    fn skips_whole_list_lines() {
+        let content = "*Edgeslist\n1 2 3\n4 5 x 6\n";
+        match parse(content, true) {
+            Err(Error::MalformedLine { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("bad target accepted"),
+        }
+        // nothing from the skipped line, not even the edge 4 - 5
+        let graph = parse(content, false).unwrap();
+        assert_eq!(graph.edges, vec![(1, 2, 1.0), (1, 3, 1.0)]);
+    }
+}
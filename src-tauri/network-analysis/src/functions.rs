@@ -0,0 +1,471 @@
+//! Degree, clustering and common-neighbour statistics.
+
+use rayon::prelude::*;
+use rayon_hash::HashMap;
+
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::progress::Control;
+use crate::triangles;
+
+fn ensure_not_empty<G: Graph>(graph: &G) -> Result<(), Error> {
+    if graph.node_count() == 0 {
+        return Err(Error::EmptyGraph);
+    }
+    Ok(())
+}
+
+/// Number of nodes, isolated ones included.
+pub fn get_node_count<G: Graph>(graph: &G) -> usize {
+    graph.node_count()
+}
+
+/// Number of edges of an undirected graph, whose edges are stored both ways.
+pub fn get_edge_count<G: Graph>(graph: &G) -> usize {
+    graph.arc_count() / 2
+}
+
+/// Number of arcs of a directed graph.
+pub fn get_arc_count<G: Graph>(graph: &G) -> usize {
+    graph.arc_count()
+}
+
+/// Mean number of neighbours per node.
+pub fn get_avg_dg<G: Graph>(graph: &G) -> Result<f64, Error> {
+    ensure_not_empty(graph)?;
+    Ok(graph.arc_count() as f64 / graph.node_count() as f64)
+}
+
+/// Largest number of neighbours of a node.
+pub fn get_max_dg<G: Graph>(graph: &G) -> Result<usize, Error> {
+    graph
+        .nodes()
+        .into_par_iter()
+        .map(|node| graph.degree(node))
+        .max()
+        .ok_or(Error::EmptyGraph)
+}
+
+/// `(degree, number of nodes with that degree)` pairs, in ascending order of degree.
+pub fn get_dg_dis<G: Graph>(graph: &G) -> Vec<(usize, usize)> {
+    let nodes: Vec<usize> = graph.nodes().collect();
+    get_dg_dis_of(graph, &nodes)
+}
+
+/// Degree distribution of the given nodes only.
+pub fn get_dg_dis_of<G: Graph>(graph: &G, nodes: &[usize]) -> Vec<(usize, usize)> {
+    let mut degree_distribution: HashMap<usize, usize> = HashMap::new();
+    for &node in nodes {
+        degree_distribution
+            .entry(graph.degree(node))
+            .and_modify(|e| *e += 1)
+            .or_insert(1);
+    }
+    let mut degree_distribution_vec: Vec<(usize, usize)> =
+        degree_distribution.into_iter().collect();
+    degree_distribution_vec.sort_by_key(|&(degree, _)| degree);
+    degree_distribution_vec
+}
+
+/// Mean number of links between the neighbours of a node, each link counted
+/// from both ends.
+pub fn get_cl_ef<G: Graph>(graph: &G) -> Result<f64, Error> {
+    get_cl_ef_of(&get_triangles(graph))
+}
+
+/// [`get_cl_ef`] from the triangle count of every node.
+pub fn get_cl_ef_of(triangles: &[usize]) -> Result<f64, Error> {
+    if triangles.is_empty() {
+        return Err(Error::EmptyGraph);
+    }
+    let sum: usize = triangles.iter().map(|&count| 2 * count).sum();
+    Ok(sum as f64 / triangles.len() as f64)
+}
+
+/// Number of triangles through every node, by node index.
+pub fn get_triangles<G: Graph>(graph: &G) -> Vec<usize> {
+    get_triangles_with(graph, &Control::new()).unwrap_or_default()
+}
+
+/// [`get_triangles`] reporting one unit of progress per node to `control`.
+pub fn get_triangles_with<G: Graph>(graph: &G, control: &Control) -> Result<Vec<usize>, Error> {
+    Ok(triangles::count(graph, control)?.nodes)
+}
+
+/// Local clustering coefficient of a node with `degree` neighbours and
+/// `triangles` connected pairs of them.
+pub fn cl_coef(triangles: usize, degree: usize) -> f64 {
+    if degree < 2 {
+        return 0.0; // no way to form a triangle with less than 2 neighbors
+    }
+    let triples = degree * (degree - 1) / 2;
+    triangles as f64 / triples as f64
+}
+
+/// Mean local clustering coefficient. Nodes with fewer than two neighbours
+/// count as 0.
+pub fn get_avg_cl_coef<G: Graph>(graph: &G) -> Result<f64, Error> {
+    get_avg_cl_coef_of(&get_all_cl_coef(graph))
+}
+
+/// [`get_avg_cl_coef`] from the coefficient of every node.
+pub fn get_avg_cl_coef_of(coefficients: &[f64]) -> Result<f64, Error> {
+    if coefficients.is_empty() {
+        return Err(Error::EmptyGraph);
+    }
+    Ok(coefficients.iter().sum::<f64>() / coefficients.len() as f64)
+}
+
+/// Local clustering coefficient of every node, by node index: the share of
+/// pairs of its neighbours that are connected. Self-loops are ignored.
+pub fn get_all_cl_coef<G: Graph>(graph: &G) -> Vec<f64> {
+    get_all_cl_coef_with(graph, &Control::new()).unwrap_or_default()
+}
+
+/// [`get_all_cl_coef`] reporting one unit of progress per node to `control`.
+pub fn get_all_cl_coef_with<G: Graph>(graph: &G, control: &Control) -> Result<Vec<f64>, Error> {
+    let triangles = get_triangles_with(graph, control)?;
+    Ok(graph
+        .nodes()
+        .map(|node| cl_coef(triangles[node], triangles::simple_degree(graph, node)))
+        .collect())
+}
+
+/// Transitivity, or global clustering coefficient: the share of paths of
+/// two edges whose ends are linked, closing a triangle.
+pub fn get_transitivity<G: Graph>(graph: &G) -> Result<f64, Error> {
+    let degrees: Vec<usize> = graph
+        .nodes()
+        .map(|node| triangles::simple_degree(graph, node))
+        .collect();
+    get_transitivity_of(&degrees, &get_triangles(graph))
+}
+
+/// [`get_transitivity`] from the degree and triangle count of every node.
+pub fn get_transitivity_of(degrees: &[usize], triangles: &[usize]) -> Result<f64, Error> {
+    if degrees.is_empty() {
+        return Err(Error::EmptyGraph);
+    }
+    let triples: usize = degrees
+        .iter()
+        .map(|&degree| degree * degree.saturating_sub(1) / 2)
+        .sum();
+    if triples == 0 {
+        return Ok(0.0);
+    }
+    // a triangle closes three paths, one at each of its nodes
+    Ok(triangles.iter().sum::<usize>() as f64 / triples as f64)
+}
+
+/// Histogram of the local clustering coefficients over `bins` equal-width
+/// bins between the smallest and largest coefficient, as `(bin, count)`
+/// pairs. Empty bins are left out.
+pub fn get_cl_coef_dis<G: Graph>(graph: &G, bins: u32) -> Result<Vec<(usize, usize)>, Error> {
+    get_cl_coef_dis_of(&get_all_cl_coef(graph), bins)
+}
+
+/// [`get_cl_coef_dis`] from the coefficient of every node.
+pub fn get_cl_coef_dis_of(coefficients: &[f64], bins: u32) -> Result<Vec<(usize, usize)>, Error> {
+    if coefficients.is_empty() {
+        return Err(Error::EmptyGraph);
+    }
+    let mut coeficients = coefficients.to_vec();
+    coeficients.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let max = coeficients[coeficients.len() - 1];
+    let min = coeficients[0];
+    let bin_size = (max - min) / bins as f64;
+    let mut cl_coef_dis: HashMap<usize, usize> = HashMap::new();
+    for coef in coeficients {
+        let bin = ((coef - min) / bin_size) as usize;
+        cl_coef_dis.entry(bin).and_modify(|e| *e += 1).or_insert(1);
+    }
+    let mut cl_coef_dis_vec: Vec<(usize, usize)> = cl_coef_dis.into_iter().collect();
+    cl_coef_dis_vec.sort_by_key(|&(bin, _)| bin);
+    Ok(cl_coef_dis_vec)
+}
+
+/// Mean local clustering coefficient per degree, as `(degree, mean)` pairs in
+/// ascending order of degree.
+pub fn get_cl_ef_dis<G: Graph>(graph: &G) -> Vec<(usize, f64)> {
+    let degrees: Vec<usize> = graph
+        .nodes()
+        .map(|node| triangles::simple_degree(graph, node))
+        .collect();
+    get_cl_ef_dis_of(&degrees, &get_all_cl_coef(graph))
+}
+
+/// [`get_cl_ef_dis`] from the degree and coefficient of every node.
+pub fn get_cl_ef_dis_of(degrees: &[usize], coefficients: &[f64]) -> Vec<(usize, f64)> {
+    let mut degree_to_coefficients: HashMap<usize, Vec<f64>> = HashMap::new();
+
+    for (&degree, coeff) in degrees.iter().zip(coefficients) {
+        degree_to_coefficients
+            .entry(degree)
+            .or_default()
+            .push(*coeff);
+    }
+
+    let mut distribution_vec: Vec<(usize, f64)> = degree_to_coefficients
+        .into_iter()
+        .map(|(degree, coeffs)| {
+            let avg_coeff = coeffs.iter().sum::<f64>() / coeffs.len() as f64;
+            (degree, avg_coeff)
+        })
+        .collect();
+
+    distribution_vec.sort_by_key(|&(degree, _)| degree);
+    distribution_vec
+}
+
+/// Number of nodes per count of links between their neighbours.
+pub fn get_cl_ds<G: Graph>(graph: &G) -> HashMap<usize, usize> {
+    let mut clustering_distribution: HashMap<usize, usize> = HashMap::new();
+    // every link between two neighbours is seen from both of them
+    let counts = get_triangles(graph).into_iter().map(|count| 2 * count);
+
+    for count in counts {
+        clustering_distribution
+            .entry(count)
+            .and_modify(|e| *e += 1)
+            .or_insert(1);
+    }
+    clustering_distribution
+}
+
+/// For every node `w`, the nodes that have `w` as a neighbour, so that the
+/// nodes sharing `w` can be listed. For undirected graphs these are the
+/// neighbours of `w` again.
+struct Sharers {
+    offsets: Vec<usize>,
+    sources: Vec<u32>,
+}
+
+impl Sharers {
+    fn new<G: Graph>(graph: &G) -> Self {
+        let node_count = graph.node_count();
+        let mut offsets = vec![0; node_count + 1];
+        for node in graph.nodes() {
+            for &neighbor in graph.neighbors(node) {
+                offsets[neighbor as usize + 1] += 1;
+            }
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+        }
+        let mut cursor = offsets[..node_count].to_vec();
+        let mut sources = vec![0; offsets[node_count]];
+        // nodes in ascending order keep every list sorted
+        for node in graph.nodes() {
+            for &neighbor in graph.neighbors(node) {
+                sources[cursor[neighbor as usize]] = node as u32;
+                cursor[neighbor as usize] += 1;
+            }
+        }
+        Sharers { offsets, sources }
+    }
+
+    fn of(&self, node: usize) -> &[u32] {
+        &self.sources[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+/// Mean number of common neighbours over all ordered pairs of nodes,
+/// including each node paired with itself.
+pub fn get_avg_cm_nb<G: Graph>(graph: &G) -> Result<f64, Error> {
+    get_avg_cm_nb_with(graph, &Control::new())
+}
+
+/// [`get_avg_cm_nb`] reporting one unit of progress per node to `control`.
+pub fn get_avg_cm_nb_with<G: Graph>(graph: &G, control: &Control) -> Result<f64, Error> {
+    ensure_not_empty(graph)?;
+    let node_count = graph.node_count();
+    control.start(node_count as u64);
+    // a node shared by k nodes is a common neighbour of k * k ordered pairs
+    let mut shared_by = vec![0u64; node_count];
+    for node in graph.nodes() {
+        control.check()?;
+        for &neighbor in graph.neighbors(node) {
+            shared_by[neighbor as usize] += 1;
+        }
+        control.advance(1);
+    }
+    let total_common: u64 = shared_by.iter().map(|&count| count * count).sum();
+    Ok(total_common as f64 / (node_count as f64 * node_count as f64))
+}
+
+/// Largest number of common neighbours of two different nodes. Unlike
+/// [`get_avg_cm_nb`], a node is not paired with itself, which would only
+/// give back [`get_max_dg`]. Earlier versions did pair a node with itself
+/// and so returned the maximum degree whenever it was larger.
+pub fn get_max_cm_ng<G: Graph>(graph: &G) -> usize {
+    get_max_cm_ng_with(graph, &Control::new()).unwrap_or(0)
+}
+
+/// [`get_max_cm_ng`] reporting one unit of progress per node to `control`.
+pub fn get_max_cm_ng_with<G: Graph>(graph: &G, control: &Control) -> Result<usize, Error> {
+    Ok(get_max_cm_pair_with(graph, control)?.map_or(0, |(_, _, common)| common))
+}
+
+/// Two different nodes with the most common neighbours, as `(first,
+/// second, common neighbours)` with `first < second`, or `None` when no two
+/// nodes share a neighbour. Ties go to the smallest pair.
+pub fn get_max_cm_pair<G: Graph>(graph: &G) -> Option<(usize, usize, usize)> {
+    get_max_cm_pair_with(graph, &Control::new()).unwrap_or_default()
+}
+
+/// [`get_max_cm_pair`] reporting one unit of progress per node to `control`.
+///
+/// Only pairs joined by a path of two edges are looked at: the common
+/// neighbours of a node are counted by walking from it to each neighbour
+/// and on to the other nodes sharing that neighbour, in time proportional
+/// to the number of such paths rather than to the square of the node count.
+pub fn get_max_cm_pair_with<G: Graph>(
+    graph: &G,
+    control: &Control,
+) -> Result<Option<(usize, usize, usize)>, Error> {
+    let node_count = graph.node_count();
+    let sharers = Sharers::new(graph);
+    control.start(node_count as u64);
+    let best = |a: Option<(usize, usize, usize)>, b: Option<(usize, usize, usize)>| match (a, b) {
+        (Some(a), Some(b)) => {
+            // most common neighbours first, then the smallest pair
+            let key = |(first, second, common): (usize, usize, usize)| {
+                (common, std::cmp::Reverse((first, second)))
+            };
+            Some(if key(b) > key(a) { b } else { a })
+        }
+        (a, b) => a.or(b),
+    };
+    // one scratch count per node and thread, each thread taking every
+    // `threads`-th node so the hubs are spread over all of them. A count is
+    // at most the degree of `first`, which fits in a node index.
+    let threads = rayon::current_num_threads().clamp(1, node_count.max(1));
+    (0..threads)
+        .into_par_iter()
+        .map(|thread| {
+            let mut counts = vec![0u32; node_count];
+            let mut touched = Vec::new();
+            let mut pair = None;
+            for first in (thread..node_count).step_by(threads) {
+                control.check()?;
+                for &shared in graph.neighbors(first) {
+                    let others = sharers.of(shared as usize);
+                    // each pair is counted from its smaller node
+                    let start = others.partition_point(|&other| other as usize <= first);
+                    for &second in &others[start..] {
+                        let second = second as usize;
+                        if counts[second] == 0 {
+                            touched.push(second);
+                        }
+                        counts[second] += 1;
+                    }
+                }
+                for second in touched.drain(..) {
+                    pair = best(pair, Some((first, second, counts[second] as usize)));
+                    counts[second] = 0;
+                }
+                control.advance(1);
+            }
+            Ok(pair)
+        })
+        .try_reduce(|| None, |a, b| Ok(best(a, b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+
+    /// A triangle 0-1-2 with a tail 2-3.
+    fn paw() -> Csr {
+        Csr::unweighted(4, &[(0, 1), (1, 2), (2, 0), (2, 3)])
+    }
+
+    #[test]
+    fn counts() {
+        let graph = paw();
+        assert_eq!(get_node_count(&graph), 4);
+        assert_eq!(get_edge_count(&graph), 4);
+        assert_eq!(get_arc_count(&graph), 8);
+    }
+
+    #[test]
+    fn degrees() {
+        let graph = paw();
+        assert_eq!(get_avg_dg(&graph).unwrap(), 2.0);
+        assert_eq!(get_max_dg(&graph).unwrap(), 3);
+        assert_eq!(get_dg_dis(&graph), vec![(1, 1), (2, 2), (3, 1)]);
+        assert_eq!(get_dg_dis_of(&graph, &[0, 3]), vec![(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn clustering() {
+        let graph = paw();
+        assert_eq!(get_all_cl_coef(&graph), vec![1.0, 1.0, 1.0 / 3.0, 0.0]);
+        assert!((get_avg_cl_coef(&graph).unwrap() - 7.0 / 12.0).abs() < 1e-12);
+        assert_eq!(get_cl_ef(&graph).unwrap(), 1.5);
+        // one triangle closes 3 of the 5 paths of two edges
+        assert_eq!(get_transitivity(&graph).unwrap(), 0.6);
+        assert_eq!(
+            get_cl_ef_dis(&graph),
+            vec![(1, 0.0), (2, 1.0), (3, 1.0 / 3.0)]
+        );
+    }
+
+    #[test]
+    fn common_neighbours() {
+        let graph = paw();
+        // every pair but (2, 3) shares exactly one neighbour
+        assert_eq!(get_max_cm_ng(&graph), 1);
+        assert_eq!(get_max_cm_pair(&graph), Some((0, 1, 1)));
+        // 8 from the diagonal, and 1 for both orders of every other pair but (2, 3)
+        assert_eq!(get_avg_cm_nb(&graph).unwrap(), (8.0 + 2.0 * 5.0) / 16.0);
+    }
+
+    #[test]
+    fn wedges_match_all_pairs() {
+        use crate::generators::{generate, Generator};
+        use crate::graph::intersection_count;
+        let model = Generator::ErdosRenyi {
+            nodes: 60,
+            probability: 0.1,
+        };
+        let graph = generate(&model, 7).unwrap().graph;
+        let mut total = 0;
+        let mut best = (0, 0, 0);
+        for first in graph.nodes() {
+            for second in graph.nodes() {
+                let common = intersection_count(graph.neighbors(first), graph.neighbors(second));
+                total += common;
+                if first < second && common > best.2 {
+                    best = (first, second, common);
+                }
+            }
+        }
+        assert_eq!(get_max_cm_pair(&graph), Some(best));
+        assert!((get_avg_cm_nb(&graph).unwrap() - total as f64 / 3600.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn progress_and_cancellation() {
+        let graph = paw();
+        let control = Control::new();
+        assert_eq!(get_max_cm_ng_with(&graph, &control).unwrap(), 1);
+        assert_eq!(control.progress(), (4, 4));
+        control.cancel();
+        assert!(matches!(
+            get_avg_cm_nb_with(&graph, &control),
+            Err(Error::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn empty_graph() {
+        let graph = Csr::unweighted(0, &[]);
+        assert!(matches!(get_avg_dg(&graph), Err(Error::EmptyGraph)));
+        assert!(matches!(get_max_dg(&graph), Err(Error::EmptyGraph)));
+        assert!(matches!(get_avg_cl_coef(&graph), Err(Error::EmptyGraph)));
+        assert!(matches!(get_avg_cm_nb(&graph), Err(Error::EmptyGraph)));
+        assert!(get_dg_dis(&graph).is_empty());
+    }
+}
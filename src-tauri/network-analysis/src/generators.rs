@@ -0,0 +1,458 @@
+//! Random graph models, for testing and as null models to compare real
+//! networks against. Every generator builds an undirected, unweighted
+//! [`Dataset`] whose node IDs are `0..n`, through the same
+//! [`DatasetBuilder`] the loaders use.
+
+use crate::dataset::{Dataset, DatasetBuilder, GraphMode};
+use crate::error::Error;
+use crate::loader::DuplicatePolicy;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon_hash::HashSet;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum Generator {
+    /// G(n, p): every pair of nodes is linked with `probability`.
+    ErdosRenyi { nodes: usize, probability: f64 },
+    /// Preferential attachment: every new node links to `edges_per_node`
+    /// existing nodes, chosen with probability proportional to their degree.
+    BarabasiAlbert { nodes: usize, edges_per_node: usize },
+    /// A ring where every node links to its `neighbors` nearest nodes, with
+    /// every edge rewired to a random node with probability `rewiring`.
+    WattsStrogatz {
+        nodes: usize,
+        neighbors: usize,
+        rewiring: f64,
+    },
+    /// Blocks of `sizes[i]` nodes, where a node of block `i` and one of block
+    /// `j` are linked with probability `probabilities[i][j]`.
+    StochasticBlockModel {
+        sizes: Vec<usize>,
+        probabilities: Vec<Vec<f64>>,
+    },
+    /// Random pairing of `degrees[node]` edge stubs per node. Self-loops and
+    /// repeated edges are dropped, so degrees can come out slightly lower.
+    Configuration { degrees: Vec<usize> },
+}
+
+impl Generator {
+    /// Short name of the model, used in default dataset IDs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::ErdosRenyi { .. } => "erdos_renyi",
+            Generator::BarabasiAlbert { .. } => "barabasi_albert",
+            Generator::WattsStrogatz { .. } => "watts_strogatz",
+            Generator::StochasticBlockModel { .. } => "stochastic_block_model",
+            Generator::Configuration { .. } => "configuration",
+        }
+    }
+}
+
+/// Draws a graph from `generator`. The same seed gives the same graph with
+/// the same version of this crate.
+pub fn generate(generator: &Generator, seed: u64) -> Result<Dataset, Error> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+    let node_count = match generator {
+        Generator::ErdosRenyi { nodes, probability } => {
+            check_probability(*probability)?;
+            sample_within(0, *nodes, *probability, &mut rng, &mut builder);
+            *nodes
+        }
+        Generator::BarabasiAlbert {
+            nodes,
+            edges_per_node,
+        } => {
+            barabasi_albert(*nodes, *edges_per_node, &mut rng, &mut builder)?;
+            *nodes
+        }
+        Generator::WattsStrogatz {
+            nodes,
+            neighbors,
+            rewiring,
+        } => {
+            watts_strogatz(*nodes, *neighbors, *rewiring, &mut rng, &mut builder)?;
+            *nodes
+        }
+        Generator::StochasticBlockModel {
+            sizes,
+            probabilities,
+        } => stochastic_block_model(sizes, probabilities, &mut rng, &mut builder)?,
+        Generator::Configuration { degrees } => {
+            configuration(degrees, &mut rng, &mut builder)?;
+            degrees.len()
+        }
+    };
+    for node in 0..node_count {
+        builder.insert_node(node);
+    }
+    Ok(builder.build(DuplicatePolicy::First)?.0)
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidArgument { message }
+}
+
+fn check_probability(probability: f64) -> Result<(), Error> {
+    if (0.0..=1.0).contains(&probability) {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "probability {} is not between 0 and 1",
+            probability
+        )))
+    }
+}
+
+/// Number of candidates skipped before the next one that is kept, when each
+/// is kept with probability `p`, `0 < p < 1`. Drawing the gaps instead of
+/// testing every pair keeps sparse graphs linear in their edge count.
+fn skip(rng: &mut StdRng, p: f64) -> u64 {
+    let r: f64 = rng.gen();
+    // ln_1p keeps its precision where 1 - p would round to 1 for tiny p
+    let gap = ((-r).ln_1p() / (-p).ln_1p()).floor();
+    gap.clamp(0.0, u64::MAX as f64) as u64
+}
+
+/// Links every pair of nodes in `first..first + count` with probability `p`.
+fn sample_within(
+    first: usize,
+    count: usize,
+    p: f64,
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) {
+    if p <= 0.0 || count < 2 {
+        return;
+    }
+    if p >= 1.0 {
+        for to in 1..count {
+            for from in 0..to {
+                builder.insert_edge(first + from, first + to, 1.0);
+            }
+        }
+        return;
+    }
+    // walks the pairs (from, to) with from < to in order, to by to
+    let (mut from, mut to) = (0u64, 1u64);
+    loop {
+        from = from.saturating_add(skip(rng, p));
+        while from >= to {
+            from -= to;
+            to += 1;
+            if to >= count as u64 {
+                return;
+            }
+        }
+        builder.insert_edge(first + from as usize, first + to as usize, 1.0);
+        from += 1;
+    }
+}
+
+/// Links every node of `a..a + a_count` to every node of `b..b + b_count`
+/// with probability `p`.
+fn sample_between(
+    (a, a_count): (usize, usize),
+    (b, b_count): (usize, usize),
+    p: f64,
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) {
+    let total = a_count as u64 * b_count as u64;
+    if p <= 0.0 || total == 0 {
+        return;
+    }
+    let mut pair = 0u64;
+    loop {
+        if p < 1.0 {
+            pair = pair.saturating_add(skip(rng, p));
+        }
+        if pair >= total {
+            return;
+        }
+        let (from, to) = (pair / b_count as u64, pair % b_count as u64);
+        builder.insert_edge(a + from as usize, b + to as usize, 1.0);
+        pair += 1;
+    }
+}
+
+fn barabasi_albert(
+    nodes: usize,
+    edges_per_node: usize,
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) -> Result<(), Error> {
+    if edges_per_node == 0 || edges_per_node >= nodes {
+        return Err(invalid(format!(
+            "edges per node must be between 1 and {}",
+            nodes.saturating_sub(1)
+        )));
+    }
+    // every node appears once per edge end, so a uniform pick from it is
+    // proportional to degree
+    let mut ends: Vec<usize> = Vec::with_capacity(2 * nodes * edges_per_node);
+    let mut targets: Vec<usize> = (0..edges_per_node).collect();
+    for node in edges_per_node..nodes {
+        for &target in &targets {
+            builder.insert_edge(node, target, 1.0);
+        }
+        ends.extend_from_slice(&targets);
+        ends.extend(std::iter::repeat(node).take(edges_per_node));
+
+        let mut chosen = HashSet::new();
+        targets.clear();
+        while targets.len() < edges_per_node {
+            let target = ends[rng.gen_range(0..ends.len())];
+            if chosen.insert(target) {
+                targets.push(target);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn watts_strogatz(
+    nodes: usize,
+    neighbors: usize,
+    rewiring: f64,
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) -> Result<(), Error> {
+    check_probability(rewiring)?;
+    if neighbors % 2 != 0 || neighbors >= nodes {
+        return Err(invalid(format!(
+            "neighbors must be even and less than the {} nodes",
+            nodes
+        )));
+    }
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut edges: HashSet<(usize, usize)> = HashSet::new();
+    let mut degrees = vec![neighbors; nodes];
+    let mut ring = Vec::with_capacity(nodes * neighbors / 2);
+    for distance in 1..=neighbors / 2 {
+        for node in 0..nodes {
+            let edge = (node, (node + distance) % nodes);
+            edges.insert(key(edge.0, edge.1));
+            ring.push(edge);
+        }
+    }
+    for (from, to) in ring {
+        if rng.gen::<f64>() >= rewiring {
+            continue;
+        }
+        // a node linked to every other node has nowhere to rewire to
+        if degrees[from] >= nodes - 1 {
+            continue;
+        }
+        let new_to = loop {
+            let candidate = rng.gen_range(0..nodes);
+            if candidate != from && !edges.contains(&key(from, candidate)) {
+                break candidate;
+            }
+        };
+        edges.remove(&key(from, to));
+        edges.insert(key(from, new_to));
+        degrees[to] -= 1;
+        degrees[new_to] += 1;
+    }
+    for (from, to) in edges {
+        builder.insert_edge(from, to, 1.0);
+    }
+    Ok(())
+}
+
+fn stochastic_block_model(
+    sizes: &[usize],
+    probabilities: &[Vec<f64>],
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) -> Result<usize, Error> {
+    if probabilities.len() != sizes.len()
+        || probabilities.iter().any(|row| row.len() != sizes.len())
+    {
+        return Err(invalid(format!(
+            "probabilities must be a {0} by {0} matrix",
+            sizes.len()
+        )));
+    }
+    for (i, row) in probabilities.iter().enumerate() {
+        for (j, &p) in row.iter().enumerate() {
+            check_probability(p)?;
+            if p != probabilities[j][i] {
+                return Err(invalid("probabilities must be symmetric".to_string()));
+            }
+        }
+    }
+    let starts: Vec<usize> = sizes
+        .iter()
+        .scan(0, |start, &size| {
+            let block = *start;
+            *start += size;
+            Some(block)
+        })
+        .collect();
+    for i in 0..sizes.len() {
+        sample_within(starts[i], sizes[i], probabilities[i][i], rng, builder);
+        for j in i + 1..sizes.len() {
+            sample_between(
+                (starts[i], sizes[i]),
+                (starts[j], sizes[j]),
+                probabilities[i][j],
+                rng,
+                builder,
+            );
+        }
+    }
+    Ok(sizes.iter().sum())
+}
+
+fn configuration(
+    degrees: &[usize],
+    rng: &mut StdRng,
+    builder: &mut DatasetBuilder,
+) -> Result<(), Error> {
+    let mut stubs: Vec<usize> = degrees
+        .iter()
+        .enumerate()
+        .flat_map(|(node, &degree)| std::iter::repeat(node).take(degree))
+        .collect();
+    if stubs.len() % 2 != 0 {
+        return Err(invalid(
+            "the degrees must add up to an even number".to_string(),
+        ));
+    }
+    stubs.shuffle(rng);
+    for pair in stubs.chunks(2) {
+        if pair[0] != pair[1] {
+            builder.insert_edge(pair[0], pair[1], 1.0);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn same_seed_same_graph() {
+        let generator = Generator::ErdosRenyi {
+            nodes: 200,
+            probability: 0.05,
+        };
+        let a = generate(&generator, 7).unwrap();
+        let b = generate(&generator, 7).unwrap();
+        assert_eq!(a.graph.raw_parts(), b.graph.raw_parts());
+    }
+
+    #[test]
+    fn erdos_renyi_extremes() {
+        let empty = generate(
+            &Generator::ErdosRenyi {
+                nodes: 10,
+                probability: 0.0,
+            },
+            1,
+        )
+        .unwrap();
+        assert_eq!((empty.node_count(), empty.edge_count()), (10, 0));
+        let complete = generate(
+            &Generator::ErdosRenyi {
+                nodes: 10,
+                probability: 1.0,
+            },
+            1,
+        )
+        .unwrap();
+        assert_eq!(complete.edge_count(), 45);
+    }
+
+    #[test]
+    fn erdos_renyi_tiny_probability() {
+        for probability in [1e-18, f64::MIN_POSITIVE] {
+            let graph = generate(
+                &Generator::ErdosRenyi {
+                    nodes: 1000,
+                    probability,
+                },
+                9,
+            )
+            .unwrap();
+            // expected 5e-13 edges or fewer
+            assert!(graph.edge_count() <= 1, "{}", graph.edge_count());
+        }
+    }
+
+    #[test]
+    fn erdos_renyi_edge_count_is_close_to_expected() {
+        let graph = generate(
+            &Generator::ErdosRenyi {
+                nodes: 2000,
+                probability: 0.01,
+            },
+            3,
+        )
+        .unwrap();
+        // expected 19990, standard deviation about 140
+        assert!((graph.edge_count() as i64 - 19990).abs() < 1000);
+    }
+
+    #[test]
+    fn barabasi_albert_edge_count() {
+        let graph = generate(
+            &Generator::BarabasiAlbert {
+                nodes: 100,
+                edges_per_node: 3,
+            },
+            5,
+        )
+        .unwrap();
+        assert_eq!(graph.edge_count(), 97 * 3);
+        assert!((3..100).all(|node| graph.graph.degree(node) >= 3));
+    }
+
+    #[test]
+    fn watts_strogatz_keeps_edge_count() {
+        for rewiring in [0.0, 0.3, 1.0] {
+            let graph = generate(
+                &Generator::WattsStrogatz {
+                    nodes: 50,
+                    neighbors: 4,
+                    rewiring,
+                },
+                11,
+            )
+            .unwrap();
+            assert_eq!(graph.edge_count(), 100);
+        }
+    }
+
+    #[test]
+    fn stochastic_block_model_without_links_between_blocks() {
+        let graph = generate(
+            &Generator::StochasticBlockModel {
+                sizes: vec![5, 5],
+                probabilities: vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+            },
+            2,
+        )
+        .unwrap();
+        assert_eq!(graph.edge_count(), 20);
+        assert!(!graph.graph.has_edge(0, 5));
+    }
+
+    #[test]
+    fn configuration_rejects_odd_degree_sum() {
+        let result = generate(
+            &Generator::Configuration {
+                degrees: vec![1, 1, 1],
+            },
+            0,
+        );
+        assert!(matches!(result, Err(Error::InvalidArgument { .. })));
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+/// Read access to a graph whose nodes are numbered `0..node_count()`.
+///
+/// The analysis functions are written against this trait. Node indices are
+/// dense; the dataset maps them back to the IDs found in the input file.
+pub trait Graph: Sync {
+    fn node_count(&self) -> usize;
+
+    /// Neighbours of `node`, sorted in ascending order.
+    fn neighbors(&self, node: usize) -> &[u32];
+
+    /// Weights matching [`Graph::neighbors`], or `None` when every edge weighs 1.
+    fn weights(&self, node: usize) -> Option<&[f64]>;
+
+    fn nodes(&self) -> Range<usize> {
+        0..self.node_count()
+    }
+
+    fn degree(&self, node: usize) -> usize {
+        self.neighbors(node).len()
+    }
+
+    /// Number of adjacency entries. An undirected edge between two different
+    /// nodes counts twice.
+    fn arc_count(&self) -> usize {
+        self.nodes().map(|node| self.degree(node)).sum()
+    }
+
+    fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.neighbors(from).binary_search(&(to as u32)).is_ok()
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let position = self.neighbors(from).binary_search(&(to as u32)).ok()?;
+        Some(self.weights(from).map_or(1.0, |weights| weights[position]))
+    }
+
+    fn weighted_neighbors(&self, node: usize) -> WeightedNeighbors<'_> {
+        WeightedNeighbors {
+            targets: self.neighbors(node),
+            weights: self.weights(node),
+            position: 0,
+        }
+    }
+
+    /// Sum of the weights of the edges of `node`.
+    fn strength(&self, node: usize) -> f64 {
+        match self.weights(node) {
+            Some(weights) => weights.iter().sum(),
+            None => self.degree(node) as f64,
+        }
+    }
+}
+
+/// Iterator over `(neighbour, weight)` pairs of a node.
+pub struct WeightedNeighbors<'a> {
+    targets: &'a [u32],
+    weights: Option<&'a [f64]>,
+    position: usize,
+}
+
+impl<'a> Iterator for WeightedNeighbors<'a> {
+    type Item = (usize, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let target = *self.targets.get(self.position)?;
+        let weight = self.weights.map_or(1.0, |weights| weights[self.position]);
+        self.position += 1;
+        Some((target as usize, weight))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.targets.len() - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Number of values present in both sorted slices.
+pub fn intersection_count(a: &[u32], b: &[u32]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
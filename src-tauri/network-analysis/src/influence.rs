@@ -0,0 +1,127 @@
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::progress::Control;
+use rand::Rng;
+use rayon_hash::HashSet;
+
+/// Independent cascade from `initial_nodes`: at every step each newly
+/// influenced node influences each of its neighbours with `probability`.
+/// Returns the influenced nodes after every step, the seeds first.
+pub fn simulate_influnce_spread<G: Graph>(
+    graph: &G,
+    initial_nodes: Vec<usize>,
+    steps: u32,
+    probability: f64,
+) -> Vec<HashSet<usize>> {
+    simulate_influnce_spread_with(graph, initial_nodes, steps, probability, &Control::new())
+        .unwrap_or_default()
+}
+
+/// [`simulate_influnce_spread`] reporting one unit of progress per step to
+/// `control`.
+pub fn simulate_influnce_spread_with<G: Graph>(
+    graph: &G,
+    initial_nodes: Vec<usize>,
+    steps: u32,
+    probability: f64,
+    control: &Control,
+) -> Result<Vec<HashSet<usize>>, Error> {
+    let mut rng = rand::thread_rng();
+    control.start(steps as u64);
+
+    let mut influence_history: Vec<HashSet<usize>> = Vec::new();
+    let mut influencing_nodes: HashSet<usize> = initial_nodes.into_iter().collect();
+    influence_history.push(influencing_nodes.clone());
+    let mut influenced_nodes: HashSet<usize> = HashSet::new();
+    for _ in 0..steps {
+        control.check()?;
+        let mut new_influencers: HashSet<usize> = HashSet::new();
+        for &node in influencing_nodes.iter() {
+            for &neigbour in graph.neighbors(node) {
+                let neigbour = neigbour as usize;
+                if influencing_nodes.contains(&neigbour) || influenced_nodes.contains(&neigbour) {
+                    continue;
+                }
+                let rand = rng.gen::<f64>();
+                if rand > probability {
+                    continue;
+                }
+                new_influencers.insert(neigbour);
+            }
+        }
+        influenced_nodes.extend(influencing_nodes.iter());
+        influence_history.push(influenced_nodes.clone());
+        influencing_nodes = new_influencers;
+        control.advance(1);
+    }
+    Ok(influence_history)
+}
+
+/// Up to `n` nodes of highest degree, no two of them adjacent, as seeds that
+/// spread influence far.
+pub fn get_best_starting_nodes<G: Graph>(graph: &G, n: u32) -> Vec<usize> {
+    let mut degrees: Vec<(usize, usize)> = graph
+        .nodes()
+        .map(|node| (node, graph.degree(node)))
+        .collect();
+
+    degrees.sort_unstable_by_key(|&(_, degree)| std::cmp::Reverse(degree));
+
+    let mut selected_nodes = Vec::new();
+    let mut selected_count = 0;
+
+    for (node, _) in degrees {
+        if selected_nodes
+            .iter()
+            .all(|&selected_node| !graph.has_edge(selected_node, node))
+        {
+            selected_nodes.push(node);
+            selected_count += 1;
+
+            if selected_count == n {
+                break;
+            }
+        }
+    }
+    selected_nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+
+    fn path() -> Csr {
+        Csr::unweighted(5, &[(0, 1), (1, 2), (2, 3), (3, 4)])
+    }
+
+    #[test]
+    fn certain_spread_advances_one_hop_per_step() {
+        let history = simulate_influnce_spread(&path(), vec![0], 3, 1.0);
+        assert_eq!(history.len(), 4);
+        let expected: [&[usize]; 4] = [&[0], &[0], &[0, 1], &[0, 1, 2]];
+        for (step, nodes) in history.iter().zip(expected) {
+            let mut step: Vec<usize> = step.iter().copied().collect();
+            step.sort_unstable();
+            assert_eq!(step, nodes);
+        }
+    }
+
+    #[test]
+    fn no_spread_without_probability() {
+        let history = simulate_influnce_spread(&path(), vec![2], 4, 0.0);
+        for step in &history {
+            assert!(step.iter().all(|&node| node == 2));
+        }
+    }
+
+    #[test]
+    fn best_starting_nodes_are_not_adjacent() {
+        // a star around 0 and a separate edge 5-6
+        let graph = Csr::unweighted(7, &[(0, 1), (0, 2), (0, 3), (0, 4), (5, 6)]);
+        let nodes = get_best_starting_nodes(&graph, 2);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0], 0);
+        assert!(nodes[1] == 5 || nodes[1] == 6);
+    }
+}
@@ -0,0 +1,102 @@
+use rayon_hash::HashMap;
+use serde::Serialize;
+
+/// A node as returned to the frontend: its ID, or its label when labels were requested.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum NodeKey {
+    Id(usize),
+    Label(String),
+}
+
+/// Node labels of a dataset, and the mapping from external string
+/// identifiers to the integer node IDs used by the analysis code.
+#[derive(Clone, Debug, Default)]
+pub struct NodeLabels {
+    ids: HashMap<String, usize>,
+    labels: HashMap<usize, String>,
+    next_id: usize,
+}
+
+impl NodeLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the node ID for `key`, assigning the next free one the first
+    /// time the key is seen. Interned keys double as the node's label.
+    pub fn intern(&mut self, key: &str) -> usize {
+        if let Some(&id) = self.ids.get(key) {
+            return id;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.ids.insert(key.to_string(), id);
+        self.labels.entry(id).or_insert_with(|| key.to_string());
+        id
+    }
+
+    pub fn set_label(&mut self, node: usize, label: String) {
+        self.labels.insert(node, label);
+    }
+
+    pub fn label(&self, node: usize) -> Option<&str> {
+        self.labels.get(&node).map(|label| label.as_str())
+    }
+
+    /// The node with the given interned identifier or label.
+    pub fn find(&self, key: &str) -> Option<usize> {
+        if let Some(&id) = self.ids.get(key) {
+            return Some(id);
+        }
+        self.labels
+            .iter()
+            .find(|(_, label)| label.as_str() == key)
+            .map(|(&node, _)| node)
+    }
+
+    /// [`NodeLabels::find`] for every identifier and label at once, for
+    /// matching many keys. A label shared by several nodes maps to the
+    /// smallest of them.
+    pub fn lookup(&self) -> HashMap<&str, usize> {
+        let mut lookup = HashMap::with_capacity(self.ids.len() + self.labels.len());
+        for (&node, label) in &self.labels {
+            if lookup
+                .get(label.as_str())
+                .map_or(true, |&found| node < found)
+            {
+                lookup.insert(label.as_str(), node);
+            }
+        }
+        for (key, &id) in &self.ids {
+            lookup.insert(key.as_str(), id);
+        }
+        lookup
+    }
+
+    /// All labelled nodes.
+    pub fn labels(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.labels
+            .iter()
+            .map(|(&node, label)| (node, label.as_str()))
+    }
+
+    /// All interned identifiers with their node IDs.
+    pub fn interned(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.ids.iter().map(|(key, &id)| (key.as_str(), id))
+    }
+
+    /// Restores an identifier returned by [`NodeLabels::interned`], without
+    /// touching the labels.
+    pub fn insert_interned(&mut self, key: String, id: usize) {
+        self.next_id = self.next_id.max(id + 1);
+        self.ids.insert(key, id);
+    }
+
+    pub fn key(&self, node: usize, labels: bool) -> NodeKey {
+        match self.label(node) {
+            Some(label) if labels => NodeKey::Label(label.to_string()),
+            _ => NodeKey::Id(node),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Graph loading and network analysis, shared by the desktop app, the
+//! `network-cli` tool and any other Rust code that needs the algorithms.
+//!
+//! A [`dataset::Dataset`] is read with [`loader::load_file`] or
+//! [`snapshot::load`], or assembled with a [`dataset::DatasetBuilder`]. Its
+//! adjacency implements [`graph::Graph`], which every algorithm in
+//! [`functions`], [`path`], [`influence`] and [`community_detection`] takes.
+//! Algorithms work on dense node indices; [`dataset::Dataset::id`] and
+//! [`dataset::Dataset::index`] convert from and to the IDs of the input file.
+//! Graphs too large for the exact metrics can be measured approximately with
+//! [`estimators`].
+//!
+//! Nothing is printed: results and failures are returned, as [`error::Error`]
+//! for the latter.
+
+pub mod attributes;
+pub mod cache;
+pub mod community_detection;
+pub mod compression;
+pub mod csr;
+pub mod dataset;
+pub mod edits;
+pub mod error;
+pub mod estimators;
+pub mod export;
+pub mod formats;
+pub mod functions;
+pub mod generators;
+pub mod graph;
+pub mod influence;
+pub mod labels;
+pub mod loader;
+pub mod path;
+pub mod progress;
+pub mod registry;
+pub mod sampling;
+pub mod snapshot;
+pub mod temporal;
+pub mod triangles;
+
+/// A file name in the temporary directory, unique to `name` and the test run.
+#[cfg(test)]
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("network-analysis-{}-{}", std::process::id(), name))
+}
@@ -0,0 +1,452 @@
+use crate::compression;
+use crate::dataset::{Dataset, DatasetBuilder, GraphMode};
+use crate::error::Error;
+use crate::formats::delimited::{self, CsvOptions};
+use crate::formats::{gml, graphml, pajek, Format};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// How to combine the weights of an edge that appears more than once in the input.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    #[default]
+    First,
+    Sum,
+    Max,
+}
+
+impl DuplicatePolicy {
+    pub fn merge(self, current: f64, new: f64) -> f64 {
+        match self {
+            DuplicatePolicy::First => current,
+            DuplicatePolicy::Sum => current + new,
+            DuplicatePolicy::Max => current.max(new),
+        }
+    }
+}
+
+/// What to do with edges from a node to itself.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfLoopPolicy {
+    #[default]
+    Keep,
+    Drop,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// Detected from the file when not set.
+    pub format: Option<Format>,
+    /// Overrides the direction declared by the file. Edge lists default to undirected.
+    pub directed: Option<bool>,
+    pub weight_column: Option<usize>,
+    /// Column of edge list timestamps. When set the dataset is temporal.
+    /// Other formats have no timestamps and fail to load when it is set.
+    pub timestamp_column: Option<usize>,
+    pub duplicates: DuplicatePolicy,
+    pub self_loops: SelfLoopPolicy,
+    pub strict: bool,
+    pub csv: CsvOptions,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoadProgress {
+    /// Bytes read from the file, before decompression.
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub lines_parsed: u64,
+    pub lines_skipped: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkippedLine {
+    pub line: usize,
+    pub content: String,
+}
+
+/// Summary of what happened to the input during a load.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LoadReport {
+    /// All lines read, including blank lines and comments.
+    pub lines_read: u64,
+    pub lines_parsed: u64,
+    pub lines_skipped: u64,
+    /// The first few skipped lines.
+    pub skipped_examples: Vec<SkippedLine>,
+    /// Edges that were already present and merged by the duplicate policy.
+    pub duplicates_merged: u64,
+    pub self_loops: u64,
+    pub self_loops_dropped: bool,
+    /// Nodes without any edges once the load is done.
+    pub isolated_nodes: usize,
+}
+
+/// Tracks a load: reports its progress every few thousand lines, stops it
+/// once `cancel` is set and collects the [`LoadReport`].
+pub struct LoadMonitor<'a> {
+    bytes_read: Arc<AtomicU64>,
+    total_bytes: u64,
+    load_report: LoadReport,
+    ticks: u64,
+    cancel: &'a AtomicBool,
+    report: &'a dyn Fn(&LoadProgress),
+}
+
+impl<'a> LoadMonitor<'a> {
+    const REPORT_EVERY: u64 = 1 << 16;
+    const SKIPPED_EXAMPLES: usize = 10;
+
+    pub fn new(
+        total_bytes: u64,
+        cancel: &'a AtomicBool,
+        report: &'a dyn Fn(&LoadProgress),
+    ) -> Self {
+        LoadMonitor {
+            bytes_read: Arc::new(AtomicU64::new(0)),
+            total_bytes,
+            load_report: LoadReport::default(),
+            ticks: 0,
+            cancel,
+            report,
+        }
+    }
+
+    /// Counts a line that held an edge or a node.
+    pub fn parsed(&mut self) -> Result<(), Error> {
+        self.load_report.lines_read += 1;
+        self.load_report.lines_parsed += 1;
+        self.tick()
+    }
+
+    /// Counts a line that could not be parsed.
+    pub fn skipped(&mut self, line: usize, content: &str) -> Result<(), Error> {
+        self.load_report.lines_read += 1;
+        self.load_report.lines_skipped += 1;
+        if self.load_report.skipped_examples.len() < Self::SKIPPED_EXAMPLES {
+            self.load_report.skipped_examples.push(SkippedLine {
+                line,
+                content: content.to_string(),
+            });
+        }
+        self.tick()
+    }
+
+    /// Counts a blank line or a comment.
+    pub fn ignored(&mut self) -> Result<(), Error> {
+        self.load_report.lines_read += 1;
+        self.tick()
+    }
+
+    /// Marks a unit of work, reporting progress and checking for cancellation.
+    /// Formats that are not read line by line call this directly.
+    pub fn tick(&mut self) -> Result<(), Error> {
+        self.ticks += 1;
+        if self.ticks % Self::REPORT_EVERY == 0 {
+            if self.cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled);
+            }
+            self.report();
+        }
+        Ok(())
+    }
+
+    /// Inserts an edge according to the self-loop policy, counting self-loops.
+    pub fn insert_edge(
+        &mut self,
+        builder: &mut DatasetBuilder,
+        from: usize,
+        to: usize,
+        weight: f64,
+        time: Option<i64>,
+        options: &LoadOptions,
+    ) {
+        if from == to {
+            self.load_report.self_loops += 1;
+            if options.self_loops == SelfLoopPolicy::Drop {
+                builder.insert_node(from);
+                return;
+            }
+        }
+        match time {
+            Some(time) => builder.insert_timed_edge(from, to, weight, time),
+            None => builder.insert_edge(from, to, weight),
+        }
+    }
+
+    fn report(&mut self) {
+        let progress = LoadProgress {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            total_bytes: self.total_bytes,
+            lines_parsed: self.load_report.lines_parsed,
+            lines_skipped: self.load_report.lines_skipped,
+        };
+        (self.report)(&progress);
+    }
+
+    fn finish(
+        &mut self,
+        dataset: &Dataset,
+        duplicates: usize,
+        options: &LoadOptions,
+    ) -> LoadReport {
+        self.report();
+        let mut load_report = std::mem::take(&mut self.load_report);
+        load_report.duplicates_merged = duplicates as u64;
+        load_report.self_loops_dropped = options.self_loops == SelfLoopPolicy::Drop;
+        load_report.isolated_nodes = dataset.isolated_node_count();
+        load_report
+    }
+}
+
+/// Reads the graph stored at `path` into a new dataset. Compressed files are
+/// decompressed while they are read. The dataset keeps the [`LoadReport`].
+pub fn load_file(
+    path: &Path,
+    options: &LoadOptions,
+    monitor: &mut LoadMonitor,
+) -> Result<Dataset, Error> {
+    let (mut reader, inner_path) = compression::open(path, monitor.bytes_read.clone())?;
+    let format = match options.format {
+        Some(format) => format,
+        None => {
+            let head = String::from_utf8_lossy(reader.fill_buf()?);
+            let first_line = head.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            Format::detect(&inner_path, first_line)
+        }
+    };
+    if options.timestamp_column.is_some() && format != Format::EdgeList {
+        return Err(Error::InvalidArgument {
+            message: format!(
+                "timestamps are only read from edge lists, not from {:?} files",
+                format
+            ),
+        });
+    }
+
+    let builder = match format {
+        Format::EdgeList => {
+            let mode = if options.directed.unwrap_or(false) {
+                GraphMode::Directed
+            } else {
+                GraphMode::Undirected
+            };
+            let mut builder = DatasetBuilder::new(mode);
+            load_edge_list(reader, &mut builder, options, monitor)?;
+            builder
+        }
+        Format::Csv => delimited::read(
+            reader,
+            &options.csv,
+            options.directed.unwrap_or(false),
+            options.strict,
+            monitor,
+        )?
+        .into_builder(options, monitor),
+        Format::GraphMl => graphml::read(reader, monitor)?.into_builder(options, monitor),
+        Format::Gml => {
+            let mut content = String::new();
+            reader.read_to_string(&mut content)?;
+            gml::read(&content, monitor)?.into_builder(options, monitor)
+        }
+        Format::Pajek => {
+            pajek::read(reader, options.strict, monitor)?.into_builder(options, monitor)
+        }
+    };
+    let (mut dataset, duplicates) = builder.build(options.duplicates)?;
+    dataset.report = Some(monitor.finish(&dataset, duplicates, options));
+    Ok(dataset)
+}
+
+/// Reads a whitespace separated edge list into `builder`.
+///
+/// The first two columns are the endpoints. When `weight_column` is set, that
+/// (zero based) column is parsed as the edge weight, otherwise every edge weighs 1.
+/// When `timestamp_column` is set, that column holds an integer timestamp.
+/// Blank lines and `#` or `%` comments are ignored. Lines that cannot be parsed,
+/// or whose weight is negative or not finite, are skipped unless `strict` is set,
+/// in which case the first one is reported as [`Error::MalformedLine`].
+pub fn load_edge_list<R: BufRead>(
+    reader: R,
+    builder: &mut DatasetBuilder,
+    options: &LoadOptions,
+    monitor: &mut LoadMonitor,
+) -> Result<(), Error> {
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if is_comment(&columns) {
+            monitor.ignored()?;
+            continue;
+        }
+        match parse_line(&columns, options.weight_column, options.timestamp_column) {
+            Some((from, to, weight, time)) => {
+                monitor.insert_edge(builder, from, to, weight, time, options);
+                monitor.parsed()?;
+            }
+            None if options.strict => {
+                return Err(Error::MalformedLine {
+                    line: index + 1,
+                    content: line,
+                })
+            }
+            None => monitor.skipped(index + 1, &line)?,
+        }
+    }
+    Ok(())
+}
+
+fn is_comment(columns: &[&str]) -> bool {
+    match columns.first() {
+        Some(first) => first.starts_with('#') || first.starts_with('%'),
+        None => true,
+    }
+}
+
+fn parse_line(
+    columns: &[&str],
+    weight_column: Option<usize>,
+    timestamp_column: Option<usize>,
+) -> Option<(usize, usize, f64, Option<i64>)> {
+    let from = columns.first()?.parse::<usize>().ok()?;
+    let to = columns.get(1)?.parse::<usize>().ok()?;
+    let weight = match weight_column {
+        Some(column) => columns.get(column)?.parse::<f64>().ok()?,
+        None => 1.0,
+    };
+    if !weight.is_finite() || weight < 0.0 {
+        return None;
+    }
+    let time = match timestamp_column {
+        Some(column) => Some(columns.get(column)?.parse::<i64>().ok()?),
+        None => None,
+    };
+    Some((from, to, weight, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    fn load(name: &str, content: &str, options: &LoadOptions) -> Result<Dataset, Error> {
+        let path = crate::temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        let cancel = AtomicBool::new(false);
+        let report = |_: &LoadProgress| {};
+        let mut monitor = LoadMonitor::new(content.len() as u64, &cancel, &report);
+        let dataset = load_file(&path, options, &mut monitor);
+        std::fs::remove_file(&path).unwrap();
+        dataset
+    }
+
+    const EDGES: &str = "# weighted\n1 2 0.5\n\n2 3 2\n3 oops\n3 4 -1\n4 4 1\n2 1 3\n";
+
+    #[test]
+    fn edge_list() {
+        let options = LoadOptions {
+            weight_column: Some(2),
+            ..Default::default()
+        };
+        let dataset = load("edge-list.txt", EDGES, &options).unwrap();
+        assert!(!dataset.is_directed());
+        assert_eq!(dataset.ids, vec![1, 2, 3, 4]);
+        assert_eq!(dataset.edge_count(), 3);
+        // the first of the duplicate edges 1-2 and 2-1 is kept
+        assert_eq!(dataset.graph.weight(0, 1), Some(0.5));
+
+        let report = dataset.report.unwrap();
+        assert_eq!(report.lines_read, 8);
+        assert_eq!(report.lines_parsed, 4);
+        assert_eq!(report.lines_skipped, 2);
+        assert_eq!(report.skipped_examples[0].line, 5);
+        assert_eq!(report.skipped_examples[1].content, "3 4 -1");
+        assert_eq!(report.duplicates_merged, 1);
+        assert_eq!(report.self_loops, 1);
+        assert_eq!(report.isolated_nodes, 0);
+    }
+
+    #[test]
+    fn policies() {
+        let options = LoadOptions {
+            weight_column: Some(2),
+            duplicates: DuplicatePolicy::Sum,
+            self_loops: SelfLoopPolicy::Drop,
+            directed: Some(true),
+            ..Default::default()
+        };
+        let dataset = load("policies.txt", EDGES, &options).unwrap();
+        assert!(dataset.is_directed());
+        // 1 -> 2 and 2 -> 1 are different arcs of a directed graph
+        assert_eq!(dataset.graph.weight(0, 1), Some(0.5));
+        assert_eq!(dataset.graph.weight(1, 0), Some(3.0));
+        assert!(!dataset.graph.has_edge(3, 3));
+        let report = dataset.report.unwrap();
+        assert!(report.self_loops_dropped);
+        assert_eq!(report.isolated_nodes, 1);
+
+        let options = LoadOptions {
+            duplicates: DuplicatePolicy::Sum,
+            weight_column: Some(2),
+            ..Default::default()
+        };
+        let dataset = load("sum.txt", EDGES, &options).unwrap();
+        assert_eq!(dataset.graph.weight(0, 1), Some(3.5));
+    }
+
+    #[test]
+    fn strict_reports_the_first_bad_line() {
+        let options = LoadOptions {
+            weight_column: Some(2),
+            strict: true,
+            ..Default::default()
+        };
+        match load("strict.txt", EDGES, &options) {
+            Err(Error::MalformedLine { line, content }) => {
+                assert_eq!(line, 5);
+                assert_eq!(content, "3 oops");
+            }
+            other => panic!("{:?}", other.map(|dataset| dataset.ids)),
+        }
+    }
+
+    #[test]
+    fn detects_the_format() {
+        let gml = "graph [ node [ id 7 label \"seven\" ] edge [ source 7 target 8 ] ]";
+        let dataset = load("detect.gml", gml, &LoadOptions::default()).unwrap();
+        assert_eq!(dataset.ids, vec![7, 8]);
+        assert_eq!(dataset.labels.label(7), Some("seven"));
+
+        // no telling extension: the first line decides
+        let pajek = "*Vertices 2\n1 a\n2 b\n*Arcs\n1 2\n";
+        let dataset = load("detect.dat", pajek, &LoadOptions::default()).unwrap();
+        assert!(dataset.is_directed());
+        assert_eq!(dataset.edge_count(), 1);
+    }
+
+    #[test]
+    fn timestamps_need_an_edge_list() {
+        let options = LoadOptions {
+            timestamp_column: Some(2),
+            ..Default::default()
+        };
+        let dataset = load("timed.txt", "1 2 100\n2 3 50\n", &options).unwrap();
+        assert_eq!(dataset.timeline.unwrap().range(), Some((50, 100)));
+
+        let gml = "graph [ edge [ source 1 target 2 ] ]";
+        assert!(matches!(
+            load("timed.gml", gml, &options),
+            Err(Error::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn empty_input() {
+        let dataset = load("empty.txt", "# nothing\n", &LoadOptions::default()).unwrap();
+        assert_eq!(dataset.node_count(), 0);
+    }
+}
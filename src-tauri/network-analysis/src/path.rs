@@ -0,0 +1,107 @@
+use crate::graph::Graph;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+#[derive(Copy, Clone, PartialEq)]
+struct State {
+    cost: f64,
+    position: usize,
+}
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.position.cmp(&other.position))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The lightest path from `start` to `end`, both included, following the edge
+/// weights; `None` when `end` cannot be reached.
+pub fn dijkstra<G: Graph>(graph: &G, start: usize, end: usize) -> Option<Vec<usize>> {
+    let mut distances = vec![f64::INFINITY; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; graph.node_count()];
+
+    distances[start] = 0.0;
+    heap.push(State {
+        cost: 0.0,
+        position: start,
+    });
+
+    while let Some(State { cost, position }) = heap.pop() {
+        if position == end {
+            let mut path = vec![end];
+            let mut current = end;
+            while let Some(predecessor) = predecessors[current] {
+                path.push(predecessor);
+                current = predecessor;
+                if current == start {
+                    break;
+                }
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if cost > distances[position] {
+            continue;
+        }
+
+        for (neighbor, weight) in graph.weighted_neighbors(position) {
+            let next_cost = cost + weight;
+            if next_cost < distances[neighbor] {
+                heap.push(State {
+                    cost: next_cost,
+                    position: neighbor,
+                });
+                distances[neighbor] = next_cost;
+                predecessors[neighbor] = Some(position);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+
+    #[test]
+    fn takes_the_lightest_path() {
+        // the direct edge 0-2 is heavier than the detour through 1
+        let graph = Csr::from_edges(4, &[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 5.0), (2, 3, 1.0)]);
+        assert_eq!(dijkstra(&graph, 0, 3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(dijkstra(&graph, 3, 0), Some(vec![3, 2, 1, 0]));
+    }
+
+    #[test]
+    fn counts_hops_without_weights() {
+        let graph = Csr::unweighted(4, &[(0, 1), (1, 2), (0, 2), (2, 3)]);
+        assert_eq!(dijkstra(&graph, 0, 3), Some(vec![0, 2, 3]));
+    }
+
+    #[test]
+    fn start_is_end() {
+        let graph = Csr::unweighted(2, &[(0, 1)]);
+        assert_eq!(dijkstra(&graph, 1, 1), Some(vec![1]));
+    }
+
+    #[test]
+    fn unreachable() {
+        let graph = Csr::unweighted(4, &[(0, 1), (2, 3)]);
+        assert_eq!(dijkstra(&graph, 0, 3), None);
+    }
+}
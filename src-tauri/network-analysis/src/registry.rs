@@ -0,0 +1,123 @@
+use crate::dataset::{Dataset, GraphMode};
+use crate::error::Error;
+use crate::loader::LoadReport;
+use crate::sampling::SampleInfo;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize)]
+pub struct DatasetInfo {
+    pub id: String,
+    pub mode: GraphMode,
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// First and last edge timestamp of a temporal dataset.
+    pub time_range: Option<(i64, i64)>,
+    /// How the dataset was sampled, when it is a sample.
+    pub sample: Option<SampleInfo>,
+    /// Number of edits applied since the dataset was loaded.
+    pub revision: u64,
+    pub active: bool,
+}
+
+/// Returned by a load: the new dataset and what happened to the input.
+#[derive(Debug, Serialize)]
+pub struct LoadSummary {
+    pub dataset: DatasetInfo,
+    pub report: LoadReport,
+}
+
+/// All datasets loaded in the session, keyed by their ID.
+/// Commands that do not name a dataset run on the active one.
+///
+/// Datasets are shared: [`Registry::get`] hands out a reference-counted
+/// handle, so an analysis can keep running on a dataset while it is
+/// replaced, edited or removed. Edits build a new dataset and swap it in
+/// with [`Registry::replace`].
+#[derive(Default)]
+pub struct Registry {
+    datasets: BTreeMap<String, Arc<Dataset>>,
+    active: Option<String>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `dataset` under `id`, replacing any dataset with the same ID,
+    /// and makes it the active one.
+    pub fn insert(&mut self, id: String, dataset: Dataset) -> DatasetInfo {
+        self.datasets.insert(id.clone(), Arc::new(dataset));
+        self.active = Some(id.clone());
+        self.info(&id).unwrap()
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), Error> {
+        self.datasets
+            .remove(id)
+            .ok_or_else(|| Error::UnknownDataset { id: id.to_string() })?;
+        if self.active.as_deref() == Some(id) {
+            self.active = None;
+        }
+        Ok(())
+    }
+
+    pub fn select(&mut self, id: &str) -> Result<DatasetInfo, Error> {
+        if !self.datasets.contains_key(id) {
+            return Err(Error::UnknownDataset { id: id.to_string() });
+        }
+        self.active = Some(id.to_string());
+        self.info(id)
+    }
+
+    /// `id` itself, or the ID of the active dataset when `id` is `None`.
+    pub fn active_id<'a>(&'a self, id: Option<&'a str>) -> Result<&'a str, Error> {
+        match id {
+            Some(id) => Ok(id),
+            None => self.active.as_deref().ok_or(Error::NoActiveDataset),
+        }
+    }
+
+    /// The dataset with the given ID, or the active one when `id` is `None`.
+    pub fn get(&self, id: Option<&str>) -> Result<Arc<Dataset>, Error> {
+        let id = self.active_id(id)?;
+        self.datasets
+            .get(id)
+            .cloned()
+            .ok_or_else(|| Error::UnknownDataset { id: id.to_string() })
+    }
+
+    /// Stores `dataset` in place of the dataset `id`, which must exist.
+    /// Analyses holding the previous dataset keep running on it.
+    pub fn replace(&mut self, id: &str, dataset: Dataset) -> Result<(), Error> {
+        let current = self
+            .datasets
+            .get_mut(id)
+            .ok_or_else(|| Error::UnknownDataset { id: id.to_string() })?;
+        *current = Arc::new(dataset);
+        Ok(())
+    }
+
+    pub fn info(&self, id: &str) -> Result<DatasetInfo, Error> {
+        let dataset = self.get(Some(id))?;
+        Ok(DatasetInfo {
+            id: id.to_string(),
+            mode: dataset.mode,
+            node_count: dataset.node_count(),
+            edge_count: dataset.edge_count(),
+            time_range: dataset.timeline.as_ref().and_then(|t| t.range()),
+            sample: dataset.sample.clone(),
+            revision: dataset.revision,
+            active: self.active.as_deref() == Some(id),
+        })
+    }
+
+    pub fn list(&self) -> Vec<DatasetInfo> {
+        self.datasets
+            .keys()
+            .filter_map(|id| self.info(id).ok())
+            .collect()
+    }
+}
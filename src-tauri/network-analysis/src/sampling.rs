@@ -0,0 +1,354 @@
+//! Smaller datasets drawn from a large one, so the quadratic metrics can run
+//! on a representative part of it.
+
+use crate::dataset::{Dataset, DatasetBuilder};
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::loader::DuplicatePolicy;
+use rand::rngs::StdRng;
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// How to pick the sample. `start` nodes are IDs from the input file; a
+/// random node is used when none is given. Directed graphs are traversed
+/// along out-arcs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Sampler {
+    /// `nodes` nodes drawn uniformly, with the edges between them.
+    Nodes { nodes: usize },
+    /// `edges` edges drawn uniformly, with their end nodes.
+    Edges { edges: usize },
+    /// Breadth-first search from `start`, up to `nodes` nodes and `depth` hops.
+    /// Stops early when the component of `start` is exhausted.
+    Snowball {
+        start: Option<usize>,
+        nodes: usize,
+        depth: Option<usize>,
+    },
+    /// A walk from `start` that goes back to it with probability `restart` at
+    /// every step, until it has visited `nodes` nodes. A walk that stops
+    /// finding new nodes jumps to a random one.
+    RandomWalk {
+        start: Option<usize>,
+        nodes: usize,
+        restart: f64,
+    },
+    /// Forest fire: every burning node sets fire to a geometrically
+    /// distributed number of its unburnt neighbours, `forward / (1 - forward)`
+    /// on average, until `nodes` nodes burnt. A fire that dies out restarts
+    /// at a random node.
+    ForestFire { nodes: usize, forward: f64 },
+}
+
+impl Sampler {
+    /// Short name of the method, used in default dataset IDs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sampler::Nodes { .. } => "nodes",
+            Sampler::Edges { .. } => "edges",
+            Sampler::Snowball { .. } => "snowball",
+            Sampler::RandomWalk { .. } => "random_walk",
+            Sampler::ForestFire { .. } => "forest_fire",
+        }
+    }
+}
+
+/// Where a sample comes from, kept with the sampled dataset.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SampleInfo {
+    /// ID of the sampled dataset.
+    pub source: String,
+    pub sampler: Sampler,
+    pub seed: u64,
+}
+
+/// Walk steps without a new node after which a random walk jumps.
+const STUCK_STEPS: usize = 1000;
+
+/// Draws a sample of `dataset`, registered as `source`. The same seed gives
+/// the same sample with the same version of this crate. The sample keeps
+/// the node IDs, labels and attributes of the sampled nodes.
+pub fn sample(
+    dataset: &Dataset,
+    source: &str,
+    sampler: &Sampler,
+    seed: u64,
+) -> Result<Dataset, Error> {
+    let node_count = dataset.node_count();
+    if node_count == 0 {
+        return Err(Error::EmptyGraph);
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let graph = &dataset.graph;
+    let start = |start: Option<usize>, rng: &mut StdRng| match start {
+        Some(start) => dataset.ensure_node(start),
+        None => Ok(rng.gen_range(0..node_count)),
+    };
+    let mut sample = match *sampler {
+        Sampler::Nodes { nodes } => {
+            check_size(nodes, node_count, "nodes")?;
+            let mut picked = index::sample(&mut rng, node_count, nodes).into_vec();
+            picked.sort_unstable();
+            induced(dataset, &picked)?
+        }
+        Sampler::Edges { edges } => {
+            // undirected edges are stored both ways, count them once
+            let all: Vec<(usize, usize)> = graph
+                .nodes()
+                .flat_map(|from| {
+                    graph
+                        .neighbors(from)
+                        .iter()
+                        .map(move |&to| (from, to as usize))
+                        .filter(|&(from, to)| dataset.is_directed() || from <= to)
+                })
+                .collect();
+            check_size(edges, all.len(), "edges")?;
+            let mut builder = DatasetBuilder::new(dataset.mode);
+            for position in index::sample(&mut rng, all.len(), edges) {
+                let (from, to) = all[position];
+                let weight = graph.weight(from, to).unwrap_or(1.0);
+                builder.insert_edge(dataset.id(from), dataset.id(to), weight);
+            }
+            finish(dataset, builder)?
+        }
+        Sampler::Snowball {
+            start: first,
+            nodes,
+            depth,
+        } => {
+            check_size(nodes, node_count, "nodes")?;
+            let first = start(first, &mut rng)?;
+            let mut visited = vec![false; node_count];
+            let mut picked = vec![first];
+            visited[first] = true;
+            let mut queue = VecDeque::from([(first, 0)]);
+            'search: while let Some((node, hops)) = queue.pop_front() {
+                if depth.map_or(false, |depth| hops >= depth) {
+                    continue;
+                }
+                for &neighbor in graph.neighbors(node) {
+                    let neighbor = neighbor as usize;
+                    if picked.len() >= nodes {
+                        break 'search;
+                    }
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        picked.push(neighbor);
+                        queue.push_back((neighbor, hops + 1));
+                    }
+                }
+            }
+            picked.sort_unstable();
+            induced(dataset, &picked)?
+        }
+        Sampler::RandomWalk {
+            start: first,
+            nodes,
+            restart,
+        } => {
+            check_size(nodes, node_count, "nodes")?;
+            check_probability(restart)?;
+            let mut home = start(first, &mut rng)?;
+            let mut visited = vec![false; node_count];
+            let mut picked = vec![home];
+            visited[home] = true;
+            let mut current = home;
+            let mut stuck = 0;
+            while picked.len() < nodes {
+                let neighbors = graph.neighbors(current);
+                current = if neighbors.is_empty() || rng.gen::<f64>() < restart {
+                    home
+                } else {
+                    neighbors[rng.gen_range(0..neighbors.len())] as usize
+                };
+                stuck += 1;
+                if stuck > STUCK_STEPS {
+                    home = rng.gen_range(0..node_count);
+                    current = home;
+                    stuck = 0;
+                }
+                if !visited[current] {
+                    visited[current] = true;
+                    picked.push(current);
+                    stuck = 0;
+                }
+            }
+            picked.sort_unstable();
+            induced(dataset, &picked)?
+        }
+        Sampler::ForestFire { nodes, forward } => {
+            check_size(nodes, node_count, "nodes")?;
+            if !(0.0..1.0).contains(&forward) {
+                return Err(Error::InvalidArgument {
+                    message: format!("forward probability {} is not in [0, 1)", forward),
+                });
+            }
+            let mut burnt = vec![false; node_count];
+            let mut picked = Vec::with_capacity(nodes);
+            let mut queue = VecDeque::new();
+            while picked.len() < nodes {
+                let node = match queue.pop_front() {
+                    Some(node) => node,
+                    None => {
+                        let node = loop {
+                            let node = rng.gen_range(0..node_count);
+                            if !burnt[node] {
+                                break node;
+                            }
+                        };
+                        burnt[node] = true;
+                        picked.push(node);
+                        node
+                    }
+                };
+                let mut spread = 0;
+                while rng.gen::<f64>() < forward {
+                    spread += 1;
+                }
+                let mut candidates: Vec<usize> = graph
+                    .neighbors(node)
+                    .iter()
+                    .map(|&neighbor| neighbor as usize)
+                    .filter(|&neighbor| !burnt[neighbor])
+                    .collect();
+                candidates.shuffle(&mut rng);
+                for neighbor in candidates.into_iter().take(spread) {
+                    if picked.len() >= nodes {
+                        break;
+                    }
+                    burnt[neighbor] = true;
+                    picked.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+            picked.sort_unstable();
+            induced(dataset, &picked)?
+        }
+    };
+    sample.sample = Some(SampleInfo {
+        source: source.to_string(),
+        sampler: sampler.clone(),
+        seed,
+    });
+    Ok(sample)
+}
+
+fn check_size(requested: usize, available: usize, what: &str) -> Result<(), Error> {
+    if requested == 0 || requested > available {
+        return Err(Error::InvalidArgument {
+            message: format!("cannot sample {} {} out of {}", requested, what, available),
+        });
+    }
+    Ok(())
+}
+
+fn check_probability(probability: f64) -> Result<(), Error> {
+    if (0.0..=1.0).contains(&probability) {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument {
+            message: format!("probability {} is not between 0 and 1", probability),
+        })
+    }
+}
+
+/// The sorted node indices `nodes` of `dataset` and all edges between them.
+fn induced(dataset: &Dataset, nodes: &[usize]) -> Result<Dataset, Error> {
+    let mut included = vec![false; dataset.node_count()];
+    for &node in nodes {
+        included[node] = true;
+    }
+    let mut builder = DatasetBuilder::new(dataset.mode);
+    for &node in nodes {
+        builder.insert_node(dataset.id(node));
+        for (neighbor, weight) in dataset.graph.weighted_neighbors(node) {
+            if included[neighbor] && (dataset.is_directed() || node <= neighbor) {
+                builder.insert_edge(dataset.id(node), dataset.id(neighbor), weight);
+            }
+        }
+    }
+    finish(dataset, builder)
+}
+
+/// Builds the sample and carries over the labels and attributes of its nodes.
+fn finish(dataset: &Dataset, builder: DatasetBuilder) -> Result<Dataset, Error> {
+    let (mut sample, _) = builder.build(DuplicatePolicy::First)?;
+    sample.labels = dataset.labels.clone();
+    let sources: Vec<Option<usize>> = sample.ids.iter().map(|&id| dataset.index(id)).collect();
+    sample.attributes = dataset.attributes.select(&sources);
+    Ok(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::GraphMode;
+
+    /// A path 10 - 11 - ... - 19 and a separate edge 20 - 21.
+    fn dataset() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        for id in 10..19 {
+            builder.insert_edge(id, id + 1, 1.0);
+        }
+        builder.insert_edge(20, 21, 2.0);
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    #[test]
+    fn node_sample_is_induced() {
+        let subgraph = sample(&dataset(), "path", &Sampler::Nodes { nodes: 12 }, 1).unwrap();
+        assert_eq!(subgraph.node_count(), 12);
+        assert_eq!(subgraph.edge_count(), 10);
+        assert_eq!(subgraph.sample.as_ref().unwrap().seed, 1);
+    }
+
+    #[test]
+    fn edge_sample_keeps_weights() {
+        let subgraph = sample(&dataset(), "path", &Sampler::Edges { edges: 10 }, 4).unwrap();
+        assert_eq!(subgraph.edge_count(), 10);
+        let (from, to) = (subgraph.index(20).unwrap(), subgraph.index(21).unwrap());
+        assert_eq!(subgraph.graph.weight(from, to), Some(2.0));
+    }
+
+    #[test]
+    fn snowball_follows_hops() {
+        let sampler = Sampler::Snowball {
+            start: Some(10),
+            nodes: 100,
+            depth: Some(3),
+        };
+        assert!(sample(&dataset(), "path", &sampler, 0).is_err());
+        let sampler = Sampler::Snowball {
+            start: Some(10),
+            nodes: 12,
+            depth: Some(3),
+        };
+        let subgraph = sample(&dataset(), "path", &sampler, 0).unwrap();
+        assert_eq!(subgraph.ids, vec![10, 11, 12, 13]);
+    }
+
+    #[test]
+    fn walk_and_fire_reach_the_requested_size() {
+        let samplers = [
+            Sampler::RandomWalk {
+                start: Some(20),
+                nodes: 5,
+                restart: 0.15,
+            },
+            Sampler::ForestFire {
+                nodes: 5,
+                forward: 0.7,
+            },
+        ];
+        for sampler in &samplers {
+            let a = sample(&dataset(), "path", sampler, 9).unwrap();
+            let b = sample(&dataset(), "path", sampler, 9).unwrap();
+            assert_eq!(a.node_count(), 5);
+            assert_eq!(a.ids, b.ids);
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Binary snapshots of loaded datasets.
+//!
+//! A snapshot starts with a magic number, the format version and the graph
+//! mode, followed by tagged sections and a CRC-32 of everything before it.
+//! All numbers are little endian. Readers skip sections they do not know, so
+//! new sections can be added without bumping the version.
+
+use crate::attributes::NodeAttributes;
+use crate::cache::{self, MetricCache, NodeMetrics};
+use crate::csr::Csr;
+use crate::dataset::{Dataset, GraphMode};
+use crate::error::Error;
+use crate::graph::Graph;
+use crate::labels::NodeLabels;
+use crate::loader::LoadReport;
+use crate::sampling::SampleInfo;
+use crate::temporal::{TimedEdge, Timeline};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"NETSNAP\0";
+const VERSION: u32 = 1;
+
+const SECTION_IDS: u32 = 1;
+const SECTION_GRAPH: u32 = 2;
+const SECTION_INCOMING: u32 = 3;
+const SECTION_LABELS: u32 = 4;
+const SECTION_INTERNED: u32 = 5;
+const SECTION_REPORT: u32 = 6;
+const SECTION_ATTRIBUTES: u32 = 7;
+const SECTION_TIMELINE: u32 = 8;
+const SECTION_SAMPLE: u32 = 9;
+const SECTION_NODE_METRICS: u32 = 10;
+const SECTION_COMMUNITIES: u32 = 11;
+
+/// Writes `dataset` to `path`.
+pub fn save(dataset: &Dataset, path: &Path) -> Result<(), Error> {
+    let mut out = ChecksumWriter {
+        inner: BufWriter::new(File::create(path)?),
+        hasher: crc32fast::Hasher::new(),
+    };
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[match dataset.mode {
+        GraphMode::Undirected => 0,
+        GraphMode::Directed => 1,
+    }])?;
+
+    let ids: Vec<u64> = dataset.ids.iter().map(|&id| id as u64).collect();
+    write_section(&mut out, SECTION_IDS, &encode_u64s(&ids))?;
+    write_csr(&mut out, SECTION_GRAPH, &dataset.graph)?;
+    if let Some(incoming) = &dataset.incoming {
+        write_csr(&mut out, SECTION_INCOMING, incoming)?;
+    }
+
+    let mut labels = Vec::new();
+    for (node, label) in dataset.labels.labels() {
+        labels.extend_from_slice(&(node as u64).to_le_bytes());
+        encode_str(&mut labels, label);
+    }
+    write_section(&mut out, SECTION_LABELS, &labels)?;
+    let mut interned = Vec::new();
+    for (key, id) in dataset.labels.interned() {
+        interned.extend_from_slice(&(id as u64).to_le_bytes());
+        encode_str(&mut interned, key);
+    }
+    write_section(&mut out, SECTION_INTERNED, &interned)?;
+    if let Some(report) = &dataset.report {
+        let report = serde_json::to_vec(report).map_err(|e| invalid(&e.to_string()))?;
+        write_section(&mut out, SECTION_REPORT, &report)?;
+    }
+    if let Some(timeline) = &dataset.timeline {
+        let mut edges = Vec::with_capacity(timeline.edges.len() * 24);
+        for edge in &timeline.edges {
+            edges.extend_from_slice(&edge.from.to_le_bytes());
+            edges.extend_from_slice(&edge.to.to_le_bytes());
+            edges.extend_from_slice(&edge.time.to_le_bytes());
+            edges.extend_from_slice(&edge.weight.to_le_bytes());
+        }
+        write_section(&mut out, SECTION_TIMELINE, &edges)?;
+    }
+    if !dataset.attributes.columns.is_empty() {
+        let attributes =
+            serde_json::to_vec(&dataset.attributes).map_err(|e| invalid(&e.to_string()))?;
+        write_section(&mut out, SECTION_ATTRIBUTES, &attributes)?;
+    }
+    if let Some(sample) = &dataset.sample {
+        let sample = serde_json::to_vec(sample).map_err(|e| invalid(&e.to_string()))?;
+        write_section(&mut out, SECTION_SAMPLE, &sample)?;
+    }
+    // cached results, so that a reloaded dataset does not compute them again
+    for (slot, metrics) in dataset.cache.computed_nodes() {
+        let mut section = Vec::with_capacity(1 + metrics.degrees.len() * 24);
+        section.push(slot as u8);
+        for node in 0..metrics.degrees.len() {
+            section.extend_from_slice(&(metrics.degrees[node] as u64).to_le_bytes());
+            section.extend_from_slice(&(metrics.triangles[node] as u64).to_le_bytes());
+            section.extend_from_slice(&metrics.clustering[node].to_le_bytes());
+        }
+        write_section(&mut out, SECTION_NODE_METRICS, &section)?;
+    }
+    if let Some(communities) = dataset.cache.computed_communities() {
+        let communities: Vec<u64> = communities.iter().map(|&c| c as u64).collect();
+        write_section(&mut out, SECTION_COMMUNITIES, &encode_u64s(&communities))?;
+    }
+
+    let checksum = out.hasher.clone().finalize();
+    out.inner.write_all(&checksum.to_le_bytes())?;
+    out.inner.flush()?;
+    Ok(())
+}
+
+/// Reads a snapshot written by [`save`].
+pub fn load(path: &Path) -> Result<Dataset, Error> {
+    let data = std::fs::read(path)?;
+    if data.len() < MAGIC.len() + 4 + 1 + 4 || &data[..MAGIC.len()] != MAGIC {
+        return Err(invalid("not a snapshot file"));
+    }
+    let (body, checksum) = data.split_at(data.len() - 4);
+    if crc32fast::hash(body) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(invalid("checksum mismatch, the file is damaged"));
+    }
+
+    let mut reader = Reader {
+        data: body,
+        position: MAGIC.len(),
+    };
+    let version = reader.u32()?;
+    if version != VERSION {
+        return Err(invalid(&format!("unsupported version {}", version)));
+    }
+    let mode = match reader.bytes(1)?[0] {
+        0 => GraphMode::Undirected,
+        1 => GraphMode::Directed,
+        other => return Err(invalid(&format!("unknown graph mode {}", other))),
+    };
+
+    let mut ids = None;
+    let mut graph = None;
+    let mut incoming = None;
+    let mut labels = NodeLabels::new();
+    let mut report = None;
+    let mut attributes = NodeAttributes::default();
+    let mut timeline = None;
+    let mut sample = None;
+    let mut node_metrics = Vec::new();
+    let mut communities = None;
+    while reader.position < body.len() {
+        let tag = reader.u32()?;
+        let length = reader.u64()? as usize;
+        let mut section = Reader {
+            data: reader.bytes(length)?,
+            position: 0,
+        };
+        match tag {
+            SECTION_IDS => {
+                let count = length / 8;
+                ids = Some(
+                    section
+                        .u64s(count)?
+                        .into_iter()
+                        .map(|id| id as usize)
+                        .collect(),
+                );
+            }
+            SECTION_GRAPH => graph = Some(read_csr(&mut section)?),
+            SECTION_INCOMING => incoming = Some(read_csr(&mut section)?),
+            SECTION_LABELS => {
+                while section.position < length {
+                    let node = section.u64()? as usize;
+                    labels.set_label(node, section.string()?);
+                }
+            }
+            SECTION_INTERNED => {
+                while section.position < length {
+                    let id = section.u64()? as usize;
+                    labels.insert_interned(section.string()?, id);
+                }
+            }
+            SECTION_REPORT => {
+                report = Some(
+                    serde_json::from_slice::<LoadReport>(section.data)
+                        .map_err(|e| invalid(&e.to_string()))?,
+                );
+            }
+            SECTION_TIMELINE => {
+                let edges = section
+                    .data
+                    .chunks_exact(24)
+                    .map(|chunk| TimedEdge {
+                        from: u32::from_le_bytes(chunk[0..4].try_into().unwrap()),
+                        to: u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+                        time: i64::from_le_bytes(chunk[8..16].try_into().unwrap()),
+                        weight: f64::from_le_bytes(chunk[16..24].try_into().unwrap()),
+                    })
+                    .collect();
+                timeline = Some(Timeline { edges });
+            }
+            SECTION_ATTRIBUTES => {
+                attributes =
+                    serde_json::from_slice(section.data).map_err(|e| invalid(&e.to_string()))?;
+            }
+            SECTION_SAMPLE => {
+                sample = Some(
+                    serde_json::from_slice::<SampleInfo>(section.data)
+                        .map_err(|e| invalid(&e.to_string()))?,
+                );
+            }
+            SECTION_NODE_METRICS => {
+                let slot = section.bytes(1)?[0] as usize;
+                let mut metrics = NodeMetrics::default();
+                for chunk in section.bytes(length - 1)?.chunks_exact(24) {
+                    let value = |range: std::ops::Range<usize>| chunk[range].try_into().unwrap();
+                    metrics
+                        .degrees
+                        .push(u64::from_le_bytes(value(0..8)) as usize);
+                    metrics
+                        .triangles
+                        .push(u64::from_le_bytes(value(8..16)) as usize);
+                    metrics.clustering.push(f64::from_le_bytes(value(16..24)));
+                }
+                node_metrics.push((slot, metrics));
+            }
+            SECTION_COMMUNITIES => {
+                let count = length / 8;
+                communities = Some(
+                    section
+                        .u64s(count)?
+                        .into_iter()
+                        .map(|community| community as usize)
+                        .collect::<Vec<usize>>(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let ids: Vec<usize> = ids.ok_or_else(|| invalid("missing node IDs"))?;
+    let graph: Csr = graph.ok_or_else(|| invalid("missing adjacency"))?;
+    if graph.node_count() != ids.len() {
+        return Err(invalid("adjacency does not match the node IDs"));
+    }
+    let node_count = ids.len() as u32;
+    let timeline_valid = timeline.as_ref().map_or(true, |timeline: &Timeline| {
+        timeline
+            .edges
+            .iter()
+            .all(|edge| edge.from < node_count && edge.to < node_count)
+    });
+    if !timeline_valid {
+        return Err(invalid("timeline does not match the node IDs"));
+    }
+    if mode == GraphMode::Directed && incoming.is_none() {
+        return Err(invalid("missing in-adjacency"));
+    }
+    let mut cache = MetricCache::default();
+    for (slot, metrics) in node_metrics {
+        if slot >= cache::VIEWS || metrics.degrees.len() != ids.len() {
+            return Err(invalid("cached node metrics do not match the node IDs"));
+        }
+        cache.restore_nodes(slot, metrics);
+    }
+    if let Some(communities) = communities {
+        if communities.len() != ids.len() || communities.iter().any(|&c| c >= ids.len()) {
+            return Err(invalid("cached communities do not match the node IDs"));
+        }
+        cache.restore_communities(communities);
+    }
+    Ok(Dataset {
+        graph,
+        incoming,
+        ids,
+        mode,
+        labels,
+        attributes,
+        timeline,
+        report,
+        sample,
+        revision: 0,
+        cache,
+    })
+}
+
+/// Hashes everything written through it.
+struct ChecksumWriter<W> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn write_section<W: Write>(out: &mut W, tag: u32, payload: &[u8]) -> Result<(), Error> {
+    out.write_all(&tag.to_le_bytes())?;
+    out.write_all(&(payload.len() as u64).to_le_bytes())?;
+    out.write_all(payload)?;
+    Ok(())
+}
+
+/// An adjacency section: node count, entry count, a weight flag, then the
+/// offsets, targets and weights arrays.
+fn write_csr<W: Write>(out: &mut W, tag: u32, csr: &Csr) -> Result<(), Error> {
+    let (offsets, targets, weights) = csr.raw_parts();
+    let length =
+        8 + 8 + 1 + offsets.len() * 8 + targets.len() * 4 + weights.map_or(0, |w| w.len() * 8);
+    out.write_all(&tag.to_le_bytes())?;
+    out.write_all(&(length as u64).to_le_bytes())?;
+    out.write_all(&(csr.node_count() as u64).to_le_bytes())?;
+    out.write_all(&(targets.len() as u64).to_le_bytes())?;
+    out.write_all(&[weights.is_some() as u8])?;
+    let offsets: Vec<u64> = offsets.iter().map(|&offset| offset as u64).collect();
+    out.write_all(&encode_u64s(&offsets))?;
+    let mut buffer = Vec::with_capacity(targets.len() * 4);
+    for target in targets {
+        buffer.extend_from_slice(&target.to_le_bytes());
+    }
+    out.write_all(&buffer)?;
+    if let Some(weights) = weights {
+        let mut buffer = Vec::with_capacity(weights.len() * 8);
+        for weight in weights {
+            buffer.extend_from_slice(&weight.to_le_bytes());
+        }
+        out.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+fn read_csr(section: &mut Reader) -> Result<Csr, Error> {
+    let node_count = section.u64()? as usize;
+    let entry_count = section.u64()? as usize;
+    let weighted = section.bytes(1)?[0] != 0;
+    let offsets = section
+        .u64s(node_count.saturating_add(1))?
+        .into_iter()
+        .map(|offset| offset as usize)
+        .collect();
+    let targets = section
+        .bytes(entry_count.saturating_mul(4))?
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    let weights = if weighted {
+        Some(
+            section
+                .bytes(entry_count.saturating_mul(8))?
+                .chunks_exact(8)
+                .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect(),
+        )
+    } else {
+        None
+    };
+    Csr::from_raw_parts(offsets, targets, weights).ok_or_else(|| invalid("invalid adjacency"))
+}
+
+fn encode_u64s(values: &[u64]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(values.len() * 8);
+    for value in values {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+    buffer
+}
+
+fn encode_str(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn u64s(&mut self, count: usize) -> Result<Vec<u64>, Error> {
+        let bytes = self.bytes(count.saturating_mul(8))?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.bytes(length)?.to_vec()).map_err(|_| invalid("invalid label"))
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidFormat {
+        message: format!("snapshot: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::DatasetBuilder;
+    use crate::loader::DuplicatePolicy;
+    use std::sync::Arc;
+
+    /// A directed, weighted, labelled and timestamped dataset.
+    fn dataset() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Directed);
+        builder.insert_timed_edge(10, 20, 0.5, 100);
+        builder.insert_timed_edge(20, 30, 2.0, 50);
+        builder.insert_timed_edge(30, 10, 1.0, 75);
+        builder.insert_node(40);
+        builder.labels.set_label(10, "ten".to_string());
+        let (mut dataset, _) = builder.build(DuplicatePolicy::First).unwrap();
+        dataset.report = Some(LoadReport {
+            lines_read: 3,
+            ..Default::default()
+        });
+        dataset
+    }
+
+    fn save_bytes(dataset: &Dataset, name: &str) -> Vec<u8> {
+        let path = crate::temp_path(name);
+        save(dataset, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    fn load_bytes(bytes: &[u8], name: &str) -> Result<Dataset, Error> {
+        let path = crate::temp_path(name);
+        std::fs::write(&path, bytes).unwrap();
+        let dataset = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        dataset
+    }
+
+    /// Replaces the trailing checksum so that `bytes` passes the check.
+    fn reseal(bytes: &mut [u8]) {
+        let body = bytes.len() - 4;
+        let checksum = crc32fast::hash(&bytes[..body]);
+        bytes[body..].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    fn message(result: Result<Dataset, Error>) -> String {
+        match result {
+            Err(Error::InvalidFormat { message }) => message,
+            Err(other) => panic!("unexpected error {}", other),
+            Ok(_) => panic!("damaged snapshot loaded"),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = dataset();
+        let bytes = save_bytes(&original, "round-trip.snap");
+        let copy = load_bytes(&bytes, "round-trip-copy.snap").unwrap();
+        assert_eq!(copy.mode, GraphMode::Directed);
+        assert_eq!(copy.ids, original.ids);
+        assert_eq!(copy.graph.raw_parts(), original.graph.raw_parts());
+        assert_eq!(
+            copy.incoming.as_ref().unwrap().raw_parts(),
+            original.incoming.as_ref().unwrap().raw_parts()
+        );
+        assert_eq!(copy.labels.label(10), Some("ten"));
+        assert_eq!(copy.report.unwrap().lines_read, 3);
+        let times: Vec<i64> = copy
+            .timeline
+            .unwrap()
+            .edges
+            .iter()
+            .map(|edge| edge.time)
+            .collect();
+        assert_eq!(times, vec![50, 75, 100]);
+    }
+
+    #[test]
+    fn round_trip_cached_metrics() {
+        use crate::dataset::DegreeMode;
+        let original = dataset();
+        let out = original.node_metrics(DegreeMode::Out);
+        let communities = original.communities();
+        let bytes = save_bytes(&original, "cached.snap");
+        let copy = load_bytes(&bytes, "cached-copy.snap").unwrap();
+        let cached = copy.cache.computed_nodes();
+        assert_eq!(cached.len(), 1);
+        let (slot, metrics) = &cached[0];
+        assert_eq!(*slot, 2);
+        assert_eq!(metrics.degrees, out.degrees);
+        assert_eq!(metrics.triangles, out.triangles);
+        assert_eq!(metrics.clustering, out.clustering);
+        assert_eq!(copy.cache.computed_communities(), Some(communities));
+        // filled from the snapshot, so asking does not compute again
+        assert!(Arc::ptr_eq(&copy.node_metrics(DegreeMode::Out), metrics));
+    }
+
+    #[test]
+    fn skips_unknown_sections() {
+        let mut bytes = save_bytes(&dataset(), "unknown-section.snap");
+        let mut section = Vec::new();
+        write_section(&mut section, 999, b"from a newer version").unwrap();
+        let body = bytes.len() - 4;
+        bytes.splice(body..body, section);
+        reseal(&mut bytes);
+        let copy = load_bytes(&bytes, "unknown-section-copy.snap").unwrap();
+        assert_eq!(copy.ids, vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut bytes = save_bytes(&dataset(), "checksum.snap");
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xff;
+        assert!(message(load_bytes(&bytes, "checksum-copy.snap")).contains("checksum"));
+    }
+
+    #[test]
+    fn truncated_section() {
+        let mut bytes = save_bytes(&dataset(), "truncated.snap");
+        // cut the file inside the last section and fix the checksum, so only
+        // the section lengths tell that something is missing
+        bytes.drain(bytes.len() - 12..bytes.len() - 4);
+        reseal(&mut bytes);
+        assert!(message(load_bytes(&bytes, "truncated-copy.snap")).contains("unexpected end"));
+    }
+
+    #[test]
+    fn not_a_snapshot() {
+        assert!(message(load_bytes(b"1 2\n2 3\n", "edges.snap")).contains("not a snapshot"));
+    }
+}
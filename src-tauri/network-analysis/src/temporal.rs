@@ -0,0 +1,264 @@
+use crate::dataset::{Dataset, DatasetBuilder, DegreeMode};
+use crate::error::Error;
+use crate::functions;
+use crate::loader::DuplicatePolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// An edge observed at a point in time, between two node indices.
+#[derive(Clone, Copy, Debug)]
+pub struct TimedEdge {
+    pub from: u32,
+    pub to: u32,
+    pub time: i64,
+    pub weight: f64,
+}
+
+/// Every edge of a temporal dataset with its timestamp, sorted by time.
+/// Repeated interactions are all kept, unlike in the merged adjacency.
+#[derive(Clone, Debug, Default)]
+pub struct Timeline {
+    pub edges: Vec<TimedEdge>,
+}
+
+impl Timeline {
+    /// The first and last timestamp.
+    pub fn range(&self) -> Option<(i64, i64)> {
+        Some((self.edges.first()?.time, self.edges.last()?.time))
+    }
+
+    /// The edges with `start <= time < end`.
+    pub fn between(&self, start: i64, end: i64) -> &[TimedEdge] {
+        let first = self.edges.partition_point(|edge| edge.time < start);
+        let last = self.edges.partition_point(|edge| edge.time < end);
+        &self.edges[first..last.max(first)]
+    }
+}
+
+/// The graph formed by the edges of `dataset` seen in `start <= time < end`.
+/// Only nodes with an edge in the window are part of it.
+pub fn window(
+    dataset: &Dataset,
+    start: i64,
+    end: i64,
+    duplicates: DuplicatePolicy,
+) -> Result<Dataset, Error> {
+    let timeline = dataset.timeline.as_ref().ok_or(Error::NotTemporal)?;
+    let mut builder = DatasetBuilder::new(dataset.mode);
+    for edge in timeline.between(start, end) {
+        builder.insert_edge(
+            dataset.id(edge.from as usize),
+            dataset.id(edge.to as usize),
+            edge.weight,
+        );
+    }
+    Ok(builder.build(duplicates)?.0)
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMetric {
+    NodeCount,
+    EdgeCount,
+    AvgDegree,
+    MaxDegree,
+    AvgClustering,
+    ClusteringEffect,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowPoint {
+    pub start: i64,
+    pub end: i64,
+    /// `None` when the metric is undefined for the window, e.g. when it is empty.
+    pub values: BTreeMap<WindowMetric, Option<f64>>,
+}
+
+/// Most windows a single series may have.
+const MAX_WINDOWS: i64 = 100_000;
+
+/// Computes `metrics` over windows of length `window`, starting every `step`
+/// from `start` until `end`. Both bounds default to the time range of the
+/// dataset. A step equal to the window length gives adjacent windows, a
+/// smaller one a sliding window.
+#[allow(clippy::too_many_arguments)]
+pub fn series(
+    dataset: &Dataset,
+    metrics: &[WindowMetric],
+    window_length: i64,
+    step: i64,
+    start: Option<i64>,
+    end: Option<i64>,
+    direction: DegreeMode,
+    duplicates: DuplicatePolicy,
+) -> Result<Vec<WindowPoint>, Error> {
+    let timeline = dataset.timeline.as_ref().ok_or(Error::NotTemporal)?;
+    if window_length <= 0 || step <= 0 {
+        return Err(Error::InvalidArgument {
+            message: "window and step must be positive".to_string(),
+        });
+    }
+    let (first, last) = match timeline.range() {
+        Some(range) => range,
+        None => return Ok(Vec::new()),
+    };
+    let start = start.unwrap_or(first);
+    let end = end.unwrap_or(last);
+    let span = match end.checked_sub(start) {
+        Some(span) if span >= 0 => span,
+        Some(_) => {
+            return Err(Error::InvalidArgument {
+                message: format!("end {} is before start {}", end, start),
+            })
+        }
+        None => {
+            return Err(Error::InvalidArgument {
+                message: format!("the time range from {} to {} is too long", start, end),
+            })
+        }
+    };
+    if span / step >= MAX_WINDOWS {
+        return Err(Error::InvalidArgument {
+            message: format!("more than {} windows, use a larger step", MAX_WINDOWS),
+        });
+    }
+
+    let mut points = Vec::new();
+    let mut window_start = start;
+    while window_start <= end {
+        let window_end = window_start.saturating_add(window_length);
+        let graph = window(dataset, window_start, window_end, duplicates)?;
+        let view = graph.view(direction);
+        let values = metrics
+            .iter()
+            .map(|&metric| {
+                let value = match metric {
+                    WindowMetric::NodeCount => Some(graph.node_count() as f64),
+                    WindowMetric::EdgeCount => Some(graph.edge_count() as f64),
+                    WindowMetric::AvgDegree => functions::get_avg_dg(&*view).ok(),
+                    WindowMetric::MaxDegree => {
+                        functions::get_max_dg(&*view).ok().map(|max| max as f64)
+                    }
+                    WindowMetric::AvgClustering => functions::get_avg_cl_coef(&*view).ok(),
+                    WindowMetric::ClusteringEffect => functions::get_cl_ef(&*view).ok(),
+                };
+                (metric, value)
+            })
+            .collect();
+        points.push(WindowPoint {
+            start: window_start,
+            end: window_end,
+            values,
+        });
+        window_start = match window_start.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::GraphMode;
+    use crate::graph::Graph;
+
+    /// The triangle 1-2-3 closing at time 20, then the edge 3-4 twice.
+    fn dataset() -> Dataset {
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        for (from, to, time) in [(1, 2, 0), (2, 3, 10), (3, 1, 20), (3, 4, 30), (4, 3, 35)] {
+            builder.insert_timed_edge(from, to, 1.0, time);
+        }
+        builder.build(DuplicatePolicy::First).unwrap().0
+    }
+
+    #[test]
+    fn windows() {
+        let dataset = dataset();
+        let timeline = dataset.timeline.as_ref().unwrap();
+        assert_eq!(timeline.range(), Some((0, 35)));
+        assert_eq!(timeline.between(10, 30).len(), 2);
+        assert!(timeline.between(30, 10).is_empty());
+
+        let early = window(&dataset, 0, 20, DuplicatePolicy::First).unwrap();
+        assert_eq!(early.ids, vec![1, 2, 3]);
+        assert_eq!(early.edge_count(), 2);
+        let late = window(&dataset, 30, 40, DuplicatePolicy::Sum).unwrap();
+        assert_eq!(late.ids, vec![3, 4]);
+        assert_eq!(late.graph.weight(0, 1), Some(2.0));
+    }
+
+    #[test]
+    fn sliding_series() {
+        let dataset = dataset();
+        let metrics = [WindowMetric::EdgeCount, WindowMetric::AvgClustering];
+        let points = series(
+            &dataset,
+            &metrics,
+            30,
+            10,
+            None,
+            None,
+            DegreeMode::Total,
+            DuplicatePolicy::First,
+        )
+        .unwrap();
+        let starts: Vec<i64> = points.iter().map(|point| point.start).collect();
+        assert_eq!(starts, vec![0, 10, 20, 30]);
+        let edges = |point: &WindowPoint| point.values[&WindowMetric::EdgeCount];
+        assert_eq!(edges(&points[0]), Some(3.0));
+        assert_eq!(points[0].values[&WindowMetric::AvgClustering], Some(1.0));
+        assert_eq!(edges(&points[3]), Some(1.0));
+    }
+
+    #[test]
+    fn bad_arguments() {
+        let dataset = dataset();
+        let run = |window_length, step, start, end| {
+            series(
+                &dataset,
+                &[WindowMetric::NodeCount],
+                window_length,
+                step,
+                start,
+                end,
+                DegreeMode::Total,
+                DuplicatePolicy::First,
+            )
+        };
+        assert!(matches!(
+            run(0, 10, Some(0), None),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            run(10, -1, Some(0), None),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            run(10, 1, Some(0), Some(MAX_WINDOWS * 2)),
+            Err(Error::InvalidArgument { .. })
+        ));
+        // the span would overflow
+        assert!(matches!(
+            run(10, i64::MAX, Some(i64::MIN), Some(i64::MAX)),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            run(10, 1, Some(i64::MIN), None),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            run(10, 1, Some(50), Some(10)),
+            Err(Error::InvalidArgument { .. })
+        ));
+
+        let mut builder = DatasetBuilder::new(GraphMode::Undirected);
+        builder.insert_edge(1, 2, 1.0);
+        let untimed = builder.build(DuplicatePolicy::First).unwrap().0;
+        assert!(matches!(
+            window(&untimed, 0, 10, DuplicatePolicy::First),
+            Err(Error::NotTemporal)
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+//! Analyses that run in the background. Submitting one returns a job ID
+//! right away; progress arrives as `job-progress` events, the end as a
+//! `job-finished` event, and the result is fetched with `get_job_result`.
+
+use network_analysis::dataset::{Dataset, DegreeMode};
+use network_analysis::error::Error;
+use network_analysis::progress::Control;
+use network_analysis::{functions, influence};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap as HashMapSTD, HashSet as HashSetSTD};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often the progress of a running job is sent to the frontend.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The analyses that can run as jobs: the ones that are quadratic in the
+/// number of nodes or iterate until convergence.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "analysis", rename_all = "snake_case")]
+pub enum Analysis {
+    AvgCommonNeighbours {
+        direction: Option<DegreeMode>,
+    },
+    /// With `pair`, the result is the pair of nodes as `get_max_cm_pair`
+    /// returns it instead of the count alone.
+    MaxCommonNeighbours {
+        direction: Option<DegreeMode>,
+        pair: Option<bool>,
+        labels: Option<bool>,
+    },
+    Clustering {
+        direction: Option<DegreeMode>,
+    },
+    Communities {
+        labels: Option<bool>,
+    },
+    /// Same as the `simulate_influnce_spread` command, seeded with node IDs
+    /// or a random node.
+    InfluenceSpread {
+        initial_nodes: Option<Vec<usize>>,
+        steps: Option<u32>,
+        probability: Option<f64>,
+        labels: Option<bool>,
+    },
+}
+
+impl Analysis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Analysis::AvgCommonNeighbours { .. } => "avg_common_neighbours",
+            Analysis::MaxCommonNeighbours { .. } => "max_common_neighbours",
+            Analysis::Clustering { .. } => "clustering",
+            Analysis::Communities { .. } => "communities",
+            Analysis::InfluenceSpread { .. } => "influence_spread",
+        }
+    }
+
+    /// Runs the analysis, checking `control` for cancellation, and returns
+    /// its result as the matching command would.
+    fn run(&self, dataset: &Dataset, control: &Control) -> Result<serde_json::Value, Error> {
+        match *self {
+            Analysis::AvgCommonNeighbours { direction } => json(&functions::get_avg_cm_nb_with(
+                &*dataset.view(direction.unwrap_or_default()),
+                control,
+            )?),
+            Analysis::MaxCommonNeighbours {
+                direction,
+                pair,
+                labels,
+            } => {
+                let view = dataset.view(direction.unwrap_or_default());
+                if !pair.unwrap_or(false) {
+                    return json(&functions::get_max_cm_ng_with(&*view, control)?);
+                }
+                let labels = labels.unwrap_or(false);
+                let key = |node: usize| dataset.labels.key(dataset.id(node), labels);
+                json(
+                    &functions::get_max_cm_pair_with(&*view, control)?
+                        .map(|(first, second, common)| (key(first), key(second), common)),
+                )
+            }
+            Analysis::Clustering { direction } => json(
+                &dataset
+                    .node_metrics_with(direction.unwrap_or_default(), control)?
+                    .clustering,
+            ),
+            Analysis::Communities { labels } => {
+                let labels = labels.unwrap_or(false);
+                let communities = dataset.communities_with(control)?;
+                let communities: HashMapSTD<_, _> = communities
+                    .iter()
+                    .enumerate()
+                    .map(|(node, &community)| {
+                        (
+                            dataset.labels.key(dataset.id(node), labels),
+                            dataset.id(community),
+                        )
+                    })
+                    .collect();
+                json(&communities)
+            }
+            Analysis::InfluenceSpread {
+                ref initial_nodes,
+                steps,
+                probability,
+                labels,
+            } => {
+                let initial_nodes = match initial_nodes {
+                    Some(nodes) => nodes
+                        .iter()
+                        .map(|&node| dataset.ensure_node(node))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => {
+                        if dataset.node_count() == 0 {
+                            return Err(Error::EmptyGraph);
+                        }
+                        vec![rand::thread_rng().gen_range(0..dataset.node_count())]
+                    }
+                };
+                let labels = labels.unwrap_or(false);
+                let simulations = influence::simulate_influnce_spread_with(
+                    &dataset.graph,
+                    initial_nodes,
+                    steps.unwrap_or(500),
+                    probability.unwrap_or(0.5),
+                    control,
+                )?;
+                let influnced_nodes: HashSetSTD<usize> =
+                    simulations.into_iter().flatten().collect();
+                let nodes_to_send = crate::neighbourhood(dataset, &influnced_nodes, labels);
+                let influnced_nodes: HashSetSTD<_> = influnced_nodes
+                    .into_iter()
+                    .map(|node| dataset.labels.key(dataset.id(node), labels))
+                    .collect();
+                json(&(nodes_to_send, influnced_nodes))
+            }
+        }
+    }
+}
+
+/// Results have string or integer map keys, so they always convert.
+fn json<T: Serialize>(value: &T) -> Result<serde_json::Value, Error> {
+    Ok(serde_json::to_value(value).expect("job results convert to JSON"))
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "state", content = "error", rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Done,
+    Failed(Error),
+    Cancelled,
+}
+
+/// What the frontend sees of a job, also the payload of `job-finished`.
+#[derive(Clone, Debug, Serialize)]
+pub struct JobInfo {
+    pub id: u64,
+    pub analysis: &'static str,
+    pub dataset: String,
+    /// Revision of the dataset the job runs on.
+    pub revision: u64,
+    #[serde(flatten)]
+    pub state: JobState,
+}
+
+/// Payload of `job-progress`: work units done and in total in the current
+/// stage of the job.
+#[derive(Clone, Debug, Serialize)]
+pub struct JobProgress {
+    pub id: u64,
+    pub done: u64,
+    pub total: u64,
+}
+
+struct Job {
+    info: Mutex<JobInfo>,
+    control: Control,
+    result: Mutex<Option<serde_json::Value>>,
+}
+
+impl Job {
+    fn info(&self) -> JobInfo {
+        self.info.lock().unwrap().clone()
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.info.lock().unwrap().state, JobState::Running)
+    }
+}
+
+/// The jobs of the session, kept until removed so their results can be
+/// fetched.
+#[derive(Default)]
+pub struct Jobs {
+    next_id: AtomicU64,
+    jobs: Mutex<BTreeMap<u64, Arc<Job>>>,
+}
+
+impl Jobs {
+    /// Starts `analysis` on `dataset` in a thread of its own and returns the
+    /// job ID. `emit` sends an event to the frontend.
+    pub fn submit<E>(&self, analysis: Analysis, id: String, dataset: Arc<Dataset>, emit: E) -> u64
+    where
+        E: Fn(&str, serde_json::Value) + Clone + Send + 'static,
+    {
+        let job_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Arc::new(Job {
+            info: Mutex::new(JobInfo {
+                id: job_id,
+                analysis: analysis.name(),
+                dataset: id,
+                revision: dataset.revision,
+                state: JobState::Running,
+            }),
+            control: Control::new(),
+            result: Mutex::new(None),
+        });
+        self.jobs.lock().unwrap().insert(job_id, job.clone());
+
+        let ticker = job.clone();
+        let emit_progress = emit.clone();
+        std::thread::spawn(move || {
+            while ticker.is_running() {
+                let (done, total) = ticker.control.progress();
+                let progress = JobProgress {
+                    id: job_id,
+                    done,
+                    total,
+                };
+                emit_progress("job-progress", serde_json::to_value(progress).unwrap());
+                std::thread::sleep(PROGRESS_INTERVAL);
+            }
+        });
+
+        std::thread::spawn(move || {
+            let outcome = analysis.run(&dataset, &job.control);
+            let state = match outcome {
+                Ok(value) => {
+                    *job.result.lock().unwrap() = Some(value);
+                    JobState::Done
+                }
+                Err(Error::Cancelled) => JobState::Cancelled,
+                Err(error) => JobState::Failed(error),
+            };
+            job.info.lock().unwrap().state = state;
+            emit("job-finished", serde_json::to_value(job.info()).unwrap());
+        });
+        job_id
+    }
+
+    fn get(&self, id: u64) -> Result<Arc<Job>, Error> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(Error::UnknownJob { id })
+    }
+
+    pub fn info(&self, id: u64) -> Result<JobInfo, Error> {
+        Ok(self.get(id)?.info())
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .values()
+            .map(|job| job.info())
+            .collect()
+    }
+
+    /// Asks the job to stop. It ends as cancelled at its next check, unless
+    /// it finishes first.
+    pub fn cancel(&self, id: u64) -> Result<(), Error> {
+        self.get(id)?.control.cancel();
+        Ok(())
+    }
+
+    /// The result of a finished job, or why there is none.
+    pub fn result(&self, id: u64) -> Result<serde_json::Value, Error> {
+        let job = self.get(id)?;
+        let state = job.info().state;
+        match state {
+            JobState::Done => Ok(job.result.lock().unwrap().clone().unwrap_or_default()),
+            JobState::Failed(error) => Err(error),
+            JobState::Cancelled => Err(Error::Cancelled),
+            JobState::Running => Err(Error::InvalidArgument {
+                message: format!("job {} is still running", id),
+            }),
+        }
+    }
+
+    /// Forgets a job and its result, cancelling it if it still runs.
+    pub fn remove(&self, id: u64) -> Result<(), Error> {
+        let job = self
+            .jobs
+            .lock()
+            .unwrap()
+            .remove(&id)
+            .ok_or(Error::UnknownJob { id })?;
+        job.control.cancel();
+        Ok(())
+    }
+}
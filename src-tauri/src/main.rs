@@ -1,243 +1,978 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod community_detection;
-mod functions;
-mod influence;
-mod path;
+mod jobs;
 
-use lazy_static::lazy_static;
-use rand::seq::SliceRandom;
+use network_analysis::dataset::{Dataset, DegreeMode, GraphMode};
+use network_analysis::error::Error;
+use network_analysis::graph::Graph;
+use network_analysis::labels::NodeKey;
+use network_analysis::registry::{DatasetInfo, LoadSummary, Registry};
+use network_analysis::{
+    attributes, compression, edits, estimators, export, formats, functions, generators, influence,
+    loader, path, sampling, snapshot, temporal,
+};
 use rand::Rng;
-use rayon_hash::HashMap;
+use serde::Serialize;
 use std::collections::HashMap as HashMapSTD;
 use std::collections::HashSet as HashSetSTD;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::sync::Mutex;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-lazy_static! {
-    static ref STATE: Mutex<HashMap<usize, HashMap<usize, usize>>> = Mutex::new(HashMap::new());
+/// State shared by the commands, managed by Tauri.
+///
+/// Commands take the registry lock only to look a dataset up or to store
+/// one. The lookup returns a shared handle, so analyses run without holding
+/// the lock and do not wait for each other. Loads and edits build their
+/// result the same way and only take the write lock to store it.
+#[derive(Default)]
+struct AppState {
+    registry: RwLock<Registry>,
+    /// Cancellation flags of the loads in progress, by the ID of the dataset
+    /// they load. `cancel_load` sets one to stop that load.
+    loads: Mutex<HashMapSTD<String, Arc<AtomicBool>>>,
+    jobs: jobs::Jobs,
 }
 
+impl AppState {
+    fn read(&self) -> RwLockReadGuard<'_, Registry> {
+        self.registry.read().unwrap()
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Registry> {
+        self.registry.write().unwrap()
+    }
+
+    /// The dataset with the given ID, or the active one.
+    fn dataset(&self, id: Option<&str>) -> Result<Arc<Dataset>, Error> {
+        self.read().get(id)
+    }
+
+    /// Replaces the dataset with the given ID, or the active one, with the
+    /// dataset `change` builds from it. `change` runs without the lock and
+    /// runs again if the dataset was replaced in the meantime, so concurrent
+    /// changes are not lost.
+    fn update<T>(
+        &self,
+        id: Option<&str>,
+        change: impl Fn(&Dataset) -> Result<(Dataset, T), Error>,
+    ) -> Result<T, Error> {
+        let id = self.read().active_id(id)?.to_string();
+        loop {
+            let current = self.dataset(Some(&id))?;
+            let (changed, result) = change(&current)?;
+            let mut registry = self.write();
+            if Arc::ptr_eq(&registry.get(Some(&id))?, &current) {
+                registry.replace(&id, changed)?;
+                return Ok(result);
+            }
+        }
+    }
+}
+
+/// Payload of `load-progress`: the progress of the load of `dataset`.
+#[derive(Clone, Serialize)]
+struct LoadEvent {
+    dataset: String,
+    #[serde(flatten)]
+    progress: loader::LoadProgress,
+}
+
+/// Loads a dataset under `name`, by default the file name. A first
+/// `load-progress` event is sent as the load starts, so its dataset ID can be
+/// passed to `cancel_load` before the load finishes.
 #[tauri::command]
-async fn load_dataset(path: String) {
+#[allow(clippy::too_many_arguments)]
+async fn load_dataset(
+    state: tauri::State<'_, AppState>,
+    window: tauri::Window,
+    path: String,
+    name: Option<String>,
+    format: Option<formats::Format>,
+    weight_column: Option<usize>,
+    timestamp_column: Option<usize>,
+    duplicates: Option<loader::DuplicatePolicy>,
+    self_loops: Option<loader::SelfLoopPolicy>,
+    directed: Option<bool>,
+    strict: Option<bool>,
+    csv: Option<formats::delimited::CsvOptions>,
+) -> Result<LoadSummary, Error> {
     println!("Loading dataset");
     println!("Path: {}", path);
-    let file = File::open(path).unwrap();
-    let mut sparse_matrix = STATE.lock().unwrap();
-
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let mut iter = line.split_whitespace();
-        if let (Some(from_str), Some(to_str)) = (iter.next(), iter.next()) {
-            if let (Ok(from), Ok(to)) = (from_str.parse::<usize>(), to_str.parse::<usize>()) {
-                sparse_matrix
-                    .entry(from)
-                    .or_insert_with(HashMap::new)
-                    .entry(to)
-                    .or_insert(1);
-                sparse_matrix
-                    .entry(to)
-                    .or_insert_with(HashMap::new)
-                    .entry(from)
-                    .or_insert(1);
-            }
+    let options = loader::LoadOptions {
+        format,
+        directed,
+        weight_column,
+        timestamp_column,
+        duplicates: duplicates.unwrap_or_default(),
+        self_loops: self_loops.unwrap_or_default(),
+        strict: strict.unwrap_or(false),
+        csv: csv.unwrap_or_default(),
+    };
+    let id = name.unwrap_or_else(|| dataset_name(&path));
+    let total_bytes = std::fs::metadata(&path)?.len();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut loads = state.loads.lock().unwrap();
+        if loads.contains_key(&id) {
+            return Err(Error::InvalidArgument {
+                message: format!("dataset {:?} is already being loaded", id),
+            });
         }
+        loads.insert(id.clone(), cancelled.clone());
     }
+    let report = |progress: &loader::LoadProgress| {
+        let event = LoadEvent {
+            dataset: id.clone(),
+            progress: progress.clone(),
+        };
+        let _ = window.emit("load-progress", event);
+    };
+    report(&loader::LoadProgress {
+        total_bytes,
+        ..Default::default()
+    });
+    let mut monitor = loader::LoadMonitor::new(total_bytes, &cancelled, &report);
+    let loaded = loader::load_file(Path::new(&path), &options, &mut monitor);
+    state.loads.lock().unwrap().remove(&id);
+    let dataset = loaded?;
     println!("Dataset loaded");
+    let report = dataset.report.clone().unwrap_or_default();
+    Ok(LoadSummary {
+        dataset: state.write().insert(id, dataset),
+        report,
+    })
+}
+
+/// Default dataset ID: the file name without its format and compression extensions.
+fn dataset_name(path: &str) -> String {
+    compression::strip_extension(Path::new(path))
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Writes the dataset to a binary snapshot that `load_snapshot` reads back
+/// much faster than the original file.
+#[tauri::command]
+async fn save_snapshot(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    snapshot::save(&dataset, Path::new(&path))
+}
+
+#[tauri::command]
+async fn load_snapshot(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    name: Option<String>,
+) -> Result<DatasetInfo, Error> {
+    println!("Loading snapshot");
+    println!("Path: {}", path);
+    let dataset = snapshot::load(Path::new(&path))?;
+    let id = name.unwrap_or_else(|| dataset_name(&path));
+    Ok(state.write().insert(id, dataset))
+}
+
+/// A seed for when none is given, small enough to survive the trip through a
+/// JavaScript number so the frontend can pass it back unchanged.
+fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..1 << 53)
+}
+
+/// Adds a random graph drawn from `generator`. The seed defaults to a random
+/// one and is part of the default dataset ID, so the graph can be drawn again.
+#[tauri::command]
+async fn generate_graph(
+    state: tauri::State<'_, AppState>,
+    generator: generators::Generator,
+    seed: Option<u64>,
+    name: Option<String>,
+) -> Result<DatasetInfo, Error> {
+    let seed = seed.unwrap_or_else(random_seed);
+    let dataset = generators::generate(&generator, seed)?;
+    let id = name.unwrap_or_else(|| format!("{}-{}", generator.name(), seed));
+    Ok(state.write().insert(id, dataset))
+}
+
+/// Stops the load of the dataset `id`. The datasets loaded before it, and
+/// other loads in progress, are kept.
+#[tauri::command]
+async fn cancel_load(state: tauri::State<'_, AppState>, id: String) -> Result<(), Error> {
+    state
+        .loads
+        .lock()
+        .unwrap()
+        .get(&id)
+        .ok_or(Error::UnknownDataset { id })?
+        .store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+async fn unload_dataset(state: tauri::State<'_, AppState>, id: String) -> Result<(), Error> {
+    state.write().remove(&id)
+}
+
+#[tauri::command]
+async fn list_datasets(state: tauri::State<'_, AppState>) -> Result<Vec<DatasetInfo>, Error> {
+    Ok(state.read().list())
+}
+
+#[tauri::command]
+async fn select_dataset(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<DatasetInfo, Error> {
+    state.write().select(&id)
 }
 
+/// The report of the load that produced the dataset, if it was read from a file.
 #[tauri::command]
-async fn get_avg_dg() -> f64 {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_avg_dg(&sparse_matrix)
+async fn get_load_report(
+    state: tauri::State<'_, AppState>,
+    dataset: Option<String>,
+) -> Result<Option<loader::LoadReport>, Error> {
+    Ok(state.dataset(dataset.as_deref())?.report.clone())
 }
 
 #[tauri::command]
-async fn get_max_dg() -> usize {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_max_dg(&sparse_matrix)
+async fn get_graph_mode(
+    state: tauri::State<'_, AppState>,
+    dataset: Option<String>,
+) -> Result<GraphMode, Error> {
+    Ok(state.dataset(dataset.as_deref())?.mode)
 }
 
 #[tauri::command]
-async fn get_cl_ef() -> f64 {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_cl_ef(&sparse_matrix)
+async fn get_node_labels(
+    state: tauri::State<'_, AppState>,
+    nodes: Vec<usize>,
+    dataset: Option<String>,
+) -> Result<HashMapSTD<usize, String>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    Ok(nodes
+        .into_iter()
+        .filter_map(|node| Some((node, dataset.labels.label(node)?.to_string())))
+        .collect())
 }
 
 #[tauri::command]
-async fn get_avg_cl_coef() -> f64 {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_avg_cl_coef(&sparse_matrix)
+async fn find_node(
+    state: tauri::State<'_, AppState>,
+    key: String,
+    dataset: Option<String>,
+) -> Result<usize, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    dataset
+        .labels
+        .find(&key)
+        .ok_or(Error::UnknownLabel { label: key })
 }
 
 #[tauri::command]
-async fn get_avg_cm_nb() -> f64 {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_avg_cm_nb(&sparse_matrix)
+async fn get_avg_dg(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<f64, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_avg_dg(&*dataset.view(direction.unwrap_or_default()))
 }
 
 #[tauri::command]
-async fn get_max_cm_ng() -> usize {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_max_cm_ng(&sparse_matrix)
+async fn get_max_dg(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<usize, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_max_dg(&*dataset.view(direction.unwrap_or_default()))
 }
 
 #[tauri::command]
-async fn get_dg_dis() -> Vec<(usize, usize)> {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_dg_dis(&sparse_matrix)
+async fn get_cl_ef(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<f64, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_cl_ef_of(
+        &dataset
+            .node_metrics(direction.unwrap_or_default())
+            .triangles,
+    )
 }
 
 #[tauri::command]
-async fn get_cl_ef_dis() -> Vec<(usize, f64)> {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_cl_ef_dis(&sparse_matrix)
+async fn get_avg_cl_coef(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<f64, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_avg_cl_coef_of(
+        &dataset
+            .node_metrics(direction.unwrap_or_default())
+            .clustering,
+    )
 }
 
+/// Share of paths of two edges that close into a triangle.
 #[tauri::command]
-async fn get_all_cl_coef() -> Vec<f64> {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_all_cl_coef(&sparse_matrix)
+async fn get_transitivity(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<f64, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let metrics = dataset.node_metrics(direction.unwrap_or_default());
+    functions::get_transitivity_of(&metrics.degrees, &metrics.triangles)
 }
 
 #[tauri::command]
-async fn get_cl_coef_dis(bins: u32) -> Vec<(usize, usize)> {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_cl_coef_dis(&sparse_matrix, bins)
+async fn get_avg_cm_nb(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<f64, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_avg_cm_nb(&*dataset.view(direction.unwrap_or_default()))
 }
 
+/// Largest number of common neighbours of two different nodes. A node is no
+/// longer paired with itself, which made the result the largest degree
+/// whenever that was higher; `get_max_dg` gives that value.
 #[tauri::command]
-async fn get_node_count() -> usize {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_node_count(&sparse_matrix)
+async fn get_max_cm_ng(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<usize, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    Ok(functions::get_max_cm_ng(
+        &*dataset.view(direction.unwrap_or_default()),
+    ))
 }
 
+/// The two nodes with the most common neighbours and how many they share.
 #[tauri::command]
-async fn get_edge_count() -> usize {
-    let sparse_matrix = STATE.lock().unwrap();
-    functions::get_edge_count(&sparse_matrix)
+async fn get_max_cm_pair(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<Option<(NodeKey, NodeKey, usize)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    let key = |node: usize| dataset.labels.key(dataset.id(node), labels);
+    Ok(
+        functions::get_max_cm_pair(&*dataset.view(direction.unwrap_or_default()))
+            .map(|(first, second, common)| (key(first), key(second), common)),
+    )
 }
 
+/// Sampling estimate of a metric with its confidence interval, for graphs
+/// too large for the exact commands. The seed defaults to a random one.
 #[tauri::command]
-async fn djikstra(start: usize, end: usize) -> Option<usize> {
+async fn estimate_metric(
+    state: tauri::State<'_, AppState>,
+    metric: estimators::Estimator,
+    budget: Option<estimators::Budget>,
+    direction: Option<DegreeMode>,
+    seed: Option<u64>,
+    dataset: Option<String>,
+) -> Result<estimators::Estimate, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    estimators::estimate(
+        &*dataset.view(direction.unwrap_or_default()),
+        metric,
+        &budget.unwrap_or_default(),
+        seed.unwrap_or_else(random_seed),
+    )
+}
+
+#[tauri::command]
+async fn get_dg_dis(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<Vec<(usize, usize)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    Ok(functions::get_dg_dis(
+        &*dataset.view(direction.unwrap_or_default()),
+    ))
+}
+
+#[tauri::command]
+async fn get_cl_ef_dis(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<Vec<(usize, f64)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let metrics = dataset.node_metrics(direction.unwrap_or_default());
+    Ok(functions::get_cl_ef_dis_of(
+        &metrics.degrees,
+        &metrics.clustering,
+    ))
+}
+
+#[tauri::command]
+async fn get_all_cl_coef(
+    state: tauri::State<'_, AppState>,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<Vec<f64>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    Ok(dataset
+        .node_metrics(direction.unwrap_or_default())
+        .clustering
+        .clone())
+}
+
+#[tauri::command]
+async fn get_cl_coef_dis(
+    state: tauri::State<'_, AppState>,
+    bins: u32,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<Vec<(usize, usize)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    functions::get_cl_coef_dis_of(
+        &dataset
+            .node_metrics(direction.unwrap_or_default())
+            .clustering,
+        bins,
+    )
+}
+
+#[tauri::command]
+async fn get_node_count(
+    state: tauri::State<'_, AppState>,
+    dataset: Option<String>,
+) -> Result<usize, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    Ok(functions::get_node_count(&dataset.graph))
+}
+
+#[tauri::command]
+async fn get_edge_count(
+    state: tauri::State<'_, AppState>,
+    dataset: Option<String>,
+) -> Result<usize, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    if dataset.is_directed() {
+        Ok(functions::get_arc_count(&dataset.graph))
+    } else {
+        Ok(functions::get_edge_count(&dataset.graph))
+    }
+}
+
+#[tauri::command]
+async fn djikstra(
+    state: tauri::State<'_, AppState>,
+    start: usize,
+    end: usize,
+    dataset: Option<String>,
+) -> Result<Option<usize>, Error> {
     println!("Djikstra");
-    let sparse_matrix = STATE.lock().unwrap();
-    let path = path::dijkstra(&sparse_matrix, start, end);
+    let dataset = state.dataset(dataset.as_deref())?;
+    let start = dataset.ensure_node(start)?;
+    let end = dataset.ensure_node(end)?;
+    let path = path::dijkstra(&dataset.graph, start, end);
     match path {
         Some(path) => {
             println!("Path: {:?}", path);
-            Some(path.len())
+            Ok(Some(path.len()))
         }
-        None => None,
+        None => Ok(None),
     }
 }
 
+/// The given nodes together with all their edges, keyed for the frontend.
+fn neighbourhood<'a>(
+    dataset: &Dataset,
+    nodes: impl IntoIterator<Item = &'a usize>,
+    labels: bool,
+) -> HashMapSTD<NodeKey, HashMapSTD<NodeKey, f64>> {
+    let key = |node: usize| dataset.labels.key(dataset.id(node), labels);
+    let mut nodes_to_send: HashMapSTD<NodeKey, HashMapSTD<NodeKey, f64>> = HashMapSTD::new();
+    for &node in nodes {
+        for (neighbor, weight) in dataset.graph.weighted_neighbors(node) {
+            nodes_to_send
+                .entry(key(node))
+                .or_default()
+                .entry(key(neighbor))
+                .or_insert(weight);
+            nodes_to_send
+                .entry(key(neighbor))
+                .or_default()
+                .entry(key(node))
+                .or_insert(weight);
+        }
+    }
+    nodes_to_send
+}
+
 #[tauri::command]
 async fn djikstra_path(
+    state: tauri::State<'_, AppState>,
     start: usize,
     end: usize,
-) -> Option<(HashMapSTD<usize, HashMapSTD<usize, usize>>, Vec<usize>)> {
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<Option<(HashMapSTD<NodeKey, HashMapSTD<NodeKey, f64>>, Vec<NodeKey>)>, Error> {
     println!("Djikstra path");
-    let sparse_matrix = STATE.lock().unwrap();
-    let path = path::dijkstra(&sparse_matrix, start, end);
+    let dataset = state.dataset(dataset.as_deref())?;
+    let start = dataset.ensure_node(start)?;
+    let end = dataset.ensure_node(end)?;
+    let labels = labels.unwrap_or(false);
+    let path = path::dijkstra(&dataset.graph, start, end);
     match path {
         Some(path) => {
-            let mut nodes_to_send: HashMapSTD<usize, HashMapSTD<usize, usize>> = HashMapSTD::new();
-            for &node in &path {
-                if let Some(neighbors) = sparse_matrix.get(&node) {
-                    for &neighbor in neighbors.keys() {
-                        nodes_to_send
-                            .entry(node)
-                            .or_insert_with(HashMapSTD::new)
-                            .entry(neighbor)
-                            .or_insert(1);
-                        nodes_to_send
-                            .entry(neighbor)
-                            .or_insert_with(HashMapSTD::new)
-                            .entry(node)
-                            .or_insert(1);
-                    }
-                }
-            }
-            Some((nodes_to_send, path))
+            let nodes_to_send = neighbourhood(&dataset, &path, labels);
+            let path = path
+                .into_iter()
+                .map(|node| dataset.labels.key(dataset.id(node), labels))
+                .collect();
+            Ok(Some((nodes_to_send, path)))
         }
-        None => None,
+        None => Ok(None),
     }
 }
 
 #[tauri::command]
 async fn simulate_influnce_spread(
+    state: tauri::State<'_, AppState>,
     initial_nodes: Option<Vec<usize>>,
+    seed_filter: Option<attributes::AttributeFilter>,
     steps: Option<u32>,
     probability: Option<f64>,
-) -> (
-    HashMapSTD<usize, HashMapSTD<usize, usize>>,
-    HashSetSTD<usize>,
-) {
-    let sparse_matrix = STATE.lock().unwrap();
-    let initial_nodes = match initial_nodes {
-        Some(v) => v,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<
+    (
+        HashMapSTD<NodeKey, HashMapSTD<NodeKey, f64>>,
+        HashSetSTD<NodeKey>,
+    ),
+    Error,
+> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let initial_nodes = match (initial_nodes, seed_filter) {
+        (Some(v), _) => v
+            .into_iter()
+            .map(|node| dataset.ensure_node(node))
+            .collect::<Result<Vec<_>, _>>()?,
+        (None, Some(filter)) => filter.matching_nodes(&dataset.attributes)?,
         _ => {
-            let mut v = Vec::new();
-            let mut rng = rand::thread_rng();
-            let keys: Vec<_> = sparse_matrix.keys().collect();
-            if let Some(&random_key) = keys.choose(&mut rng) {
-                v.push(*random_key)
+            if dataset.node_count() == 0 {
+                return Err(Error::EmptyGraph);
             }
-            v
+            vec![rand::thread_rng().gen_range(0..dataset.node_count())]
         }
     };
     let steps = steps.unwrap_or(500);
     let probability = probability.unwrap_or(0.5);
+    let labels = labels.unwrap_or(false);
     let simulations =
-        influence::simulate_influnce_spread(&sparse_matrix, initial_nodes, steps, probability);
-    let influnced_nodes: HashSetSTD<usize> = simulations.into_iter().flat_map(|hs| hs).collect();
-    let mut nodes_to_send: HashMapSTD<usize, HashMapSTD<usize, usize>> = HashMapSTD::new();
-    for &node in &influnced_nodes {
-        if let Some(neighbors) = sparse_matrix.get(&node) {
-            for &neighbor in neighbors.keys() {
-                nodes_to_send
-                    .entry(node)
-                    .or_insert_with(HashMapSTD::new)
-                    .entry(neighbor)
-                    .or_insert(1);
-                nodes_to_send
-                    .entry(neighbor)
-                    .or_insert_with(HashMapSTD::new)
-                    .entry(node)
-                    .or_insert(1);
-            }
-        }
+        influence::simulate_influnce_spread(&dataset.graph, initial_nodes, steps, probability);
+    let influnced_nodes: HashSetSTD<usize> = simulations.into_iter().flatten().collect();
+    let nodes_to_send = neighbourhood(&dataset, &influnced_nodes, labels);
+    let influnced_nodes = influnced_nodes
+        .into_iter()
+        .map(|node| dataset.labels.key(dataset.id(node), labels))
+        .collect();
+    Ok((nodes_to_send, influnced_nodes))
+}
+
+#[tauri::command]
+async fn get_best_starting_nodes(
+    state: tauri::State<'_, AppState>,
+    n: u32,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<Vec<NodeKey>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    Ok(influence::get_best_starting_nodes(&dataset.graph, n)
+        .into_iter()
+        .map(|node| dataset.labels.key(dataset.id(node), labels))
+        .collect())
+}
+
+#[tauri::command]
+async fn louvain_community_detection(
+    state: tauri::State<'_, AppState>,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<HashMapSTD<NodeKey, usize>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    Ok(dataset
+        .communities()
+        .iter()
+        .enumerate()
+        .map(|(node, &community)| {
+            (
+                dataset.labels.key(dataset.id(node), labels),
+                dataset.id(community),
+            )
+        })
+        .collect())
+}
+
+/// Writes the dataset, or the part of it selected by `scope`, to `path`.
+#[tauri::command]
+async fn export_graph(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    format: export::ExportFormat,
+    scope: Option<export::ExportScope>,
+    attributes: Option<Vec<export::NodeAttribute>>,
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    export::export(
+        &dataset,
+        Path::new(&path),
+        format,
+        &scope.unwrap_or_default(),
+        &attributes.unwrap_or_default(),
+    )
+}
+
+/// Joins the columns of a node attribute CSV onto the dataset.
+#[tauri::command]
+async fn load_node_attributes(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    key_column: Option<formats::delimited::Column>,
+    match_by: Option<attributes::MatchBy>,
+    delimiter: Option<char>,
+    dataset: Option<String>,
+) -> Result<attributes::AttributeSummary, Error> {
+    let key_column = key_column.unwrap_or(formats::delimited::Column::Index(0));
+    state.update(dataset.as_deref(), |current| {
+        attributes::load(
+            current,
+            Path::new(&path),
+            &key_column,
+            match_by.unwrap_or_default(),
+            delimiter.unwrap_or(','),
+        )
+    })
+}
+
+#[tauri::command]
+async fn list_node_attributes(
+    state: tauri::State<'_, AppState>,
+    dataset: Option<String>,
+) -> Result<Vec<attributes::ColumnInfo>, Error> {
+    Ok(state.dataset(dataset.as_deref())?.attributes.info())
+}
+
+#[tauri::command]
+async fn get_node_attributes(
+    state: tauri::State<'_, AppState>,
+    nodes: Vec<usize>,
+    dataset: Option<String>,
+) -> Result<HashMapSTD<usize, HashMapSTD<String, attributes::AttributeValue>>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let mut values = HashMapSTD::new();
+    for node in nodes {
+        let index = dataset.ensure_node(node)?;
+        let row = dataset
+            .attributes
+            .columns
+            .iter()
+            .filter_map(|column| Some((column.name.clone(), column.value(index)?)))
+            .collect();
+        values.insert(node, row);
     }
-    (nodes_to_send, influnced_nodes)
+    Ok(values)
 }
 
 #[tauri::command]
-async fn get_best_starting_nodes(n: u32) -> Vec<usize> {
-    let sparse_matrix = STATE.lock().unwrap();
-    influence::get_best_starting_nodes(&sparse_matrix, n)
+async fn filter_nodes(
+    state: tauri::State<'_, AppState>,
+    filter: attributes::AttributeFilter,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<Vec<NodeKey>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    Ok(filter
+        .matching_nodes(&dataset.attributes)?
+        .into_iter()
+        .map(|node| dataset.labels.key(dataset.id(node), labels))
+        .collect())
 }
 
+/// The nodes of every distinct value of the attribute.
 #[tauri::command]
-async fn louvain_community_detection() -> HashMapSTD<usize, usize> {
-    let sparse_matrix = STATE.lock().unwrap();
-    HashMapSTD::from_iter(community_detection::louvain_community_detection(
-        &sparse_matrix,
-    ))
+async fn group_nodes(
+    state: tauri::State<'_, AppState>,
+    column: String,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<Vec<(attributes::AttributeValue, Vec<NodeKey>)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    Ok(dataset
+        .attributes
+        .column(&column)?
+        .groups()
+        .into_iter()
+        .map(|(value, nodes)| {
+            let nodes = nodes
+                .into_iter()
+                .map(|node| dataset.labels.key(dataset.id(node), labels))
+                .collect();
+            (value, nodes)
+        })
+        .collect())
+}
+
+/// The degree distribution of the nodes of every distinct value of the attribute.
+#[tauri::command]
+async fn get_dg_dis_by_attribute(
+    state: tauri::State<'_, AppState>,
+    column: String,
+    direction: Option<DegreeMode>,
+    dataset: Option<String>,
+) -> Result<Vec<(attributes::AttributeValue, Vec<(usize, usize)>)>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let view = dataset.view(direction.unwrap_or_default());
+    Ok(dataset
+        .attributes
+        .column(&column)?
+        .groups()
+        .into_iter()
+        .map(|(value, nodes)| (value, functions::get_dg_dis_of(&*view, &nodes)))
+        .collect())
+}
+
+/// A display colour for every node that has a value for the attribute.
+#[tauri::command]
+async fn colour_by_attribute(
+    state: tauri::State<'_, AppState>,
+    column: String,
+    labels: Option<bool>,
+    dataset: Option<String>,
+) -> Result<HashMapSTD<NodeKey, String>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    let labels = labels.unwrap_or(false);
+    Ok(attributes::colours(dataset.attributes.column(&column)?)
+        .into_iter()
+        .map(|(node, colour)| (dataset.labels.key(dataset.id(node), labels), colour))
+        .collect())
+}
+
+/// Adds the graph of the edges seen in `start <= time < end` as a new dataset,
+/// so every other command can run on it.
+#[tauri::command]
+async fn select_time_window(
+    state: tauri::State<'_, AppState>,
+    start: i64,
+    end: i64,
+    name: Option<String>,
+    duplicates: Option<loader::DuplicatePolicy>,
+    dataset: Option<String>,
+) -> Result<DatasetInfo, Error> {
+    let (source_id, source) = {
+        let registry = state.read();
+        let source_id = registry.active_id(dataset.as_deref())?.to_string();
+        let source = registry.get(Some(&source_id))?;
+        (source_id, source)
+    };
+    let mut window = temporal::window(&source, start, end, duplicates.unwrap_or_default())?;
+    window.labels = source.labels.clone();
+    let id = name.unwrap_or_else(|| format!("{}@{}..{}", source_id, start, end));
+    Ok(state.write().insert(id, window))
+}
+
+/// Adds a sample of the dataset as a new dataset. The seed defaults to a
+/// random one; it is recorded with the sample together with the method.
+#[tauri::command]
+async fn sample_dataset(
+    state: tauri::State<'_, AppState>,
+    sampler: sampling::Sampler,
+    seed: Option<u64>,
+    name: Option<String>,
+    dataset: Option<String>,
+) -> Result<DatasetInfo, Error> {
+    let seed = seed.unwrap_or_else(random_seed);
+    let (source_id, source) = {
+        let registry = state.read();
+        let source_id = registry.active_id(dataset.as_deref())?.to_string();
+        let source = registry.get(Some(&source_id))?;
+        (source_id, source)
+    };
+    let sample = sampling::sample(&source, &source_id, &sampler, seed)?;
+    let id = name.unwrap_or_else(|| format!("{}~{}-{}", source_id, sampler.name(), seed));
+    Ok(state.write().insert(id, sample))
+}
+
+/// Applies a batch of node and edge edits to the dataset, all or none of them.
+#[tauri::command]
+async fn edit_graph(
+    state: tauri::State<'_, AppState>,
+    edits: Vec<edits::Edit>,
+    dataset: Option<String>,
+) -> Result<edits::EditSummary, Error> {
+    state.update(dataset.as_deref(), |current| edits::apply(current, &edits))
+}
+
+#[tauri::command]
+async fn add_node(
+    state: tauri::State<'_, AppState>,
+    node: usize,
+    dataset: Option<String>,
+) -> Result<edits::EditSummary, Error> {
+    edit_graph(state, vec![edits::Edit::AddNode { node }], dataset).await
+}
+
+#[tauri::command]
+async fn remove_node(
+    state: tauri::State<'_, AppState>,
+    node: usize,
+    dataset: Option<String>,
+) -> Result<edits::EditSummary, Error> {
+    edit_graph(state, vec![edits::Edit::RemoveNode { node }], dataset).await
+}
+
+#[tauri::command]
+async fn add_edge(
+    state: tauri::State<'_, AppState>,
+    from: usize,
+    to: usize,
+    weight: Option<f64>,
+    dataset: Option<String>,
+) -> Result<edits::EditSummary, Error> {
+    edit_graph(
+        state,
+        vec![edits::Edit::AddEdge { from, to, weight }],
+        dataset,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn remove_edge(
+    state: tauri::State<'_, AppState>,
+    from: usize,
+    to: usize,
+    dataset: Option<String>,
+) -> Result<edits::EditSummary, Error> {
+    edit_graph(state, vec![edits::Edit::RemoveEdge { from, to }], dataset).await
+}
+
+/// Metrics of a temporal dataset over consecutive or sliding time windows.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn get_metric_series(
+    state: tauri::State<'_, AppState>,
+    metrics: Vec<temporal::WindowMetric>,
+    window: i64,
+    step: Option<i64>,
+    start: Option<i64>,
+    end: Option<i64>,
+    direction: Option<DegreeMode>,
+    duplicates: Option<loader::DuplicatePolicy>,
+    dataset: Option<String>,
+) -> Result<Vec<temporal::WindowPoint>, Error> {
+    let dataset = state.dataset(dataset.as_deref())?;
+    temporal::series(
+        &dataset,
+        &metrics,
+        window,
+        step.unwrap_or(window),
+        start,
+        end,
+        direction.unwrap_or_default(),
+        duplicates.unwrap_or_default(),
+    )
+}
+
+/// Starts `analysis` in the background and returns the job ID. The job
+/// works on the dataset as it is now; later edits do not affect it.
+#[tauri::command]
+async fn submit_job(
+    state: tauri::State<'_, AppState>,
+    window: tauri::Window,
+    analysis: jobs::Analysis,
+    dataset: Option<String>,
+) -> Result<u64, Error> {
+    let (id, dataset) = {
+        let registry = state.read();
+        let id = registry.active_id(dataset.as_deref())?.to_string();
+        let dataset = registry.get(Some(&id))?;
+        (id, dataset)
+    };
+    let emit = move |event: &str, payload: serde_json::Value| {
+        let _ = window.emit(event, payload);
+    };
+    Ok(state.jobs.submit(analysis, id, dataset, emit))
+}
+
+#[tauri::command]
+async fn get_job(state: tauri::State<'_, AppState>, id: u64) -> Result<jobs::JobInfo, Error> {
+    state.jobs.info(id)
+}
+
+#[tauri::command]
+async fn list_jobs(state: tauri::State<'_, AppState>) -> Result<Vec<jobs::JobInfo>, Error> {
+    Ok(state.jobs.list())
+}
+
+#[tauri::command]
+async fn get_job_result(
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> Result<serde_json::Value, Error> {
+    state.jobs.result(id)
+}
+
+#[tauri::command]
+async fn cancel_job(state: tauri::State<'_, AppState>, id: u64) -> Result<(), Error> {
+    state.jobs.cancel(id)
+}
+
+#[tauri::command]
+async fn remove_job(state: tauri::State<'_, AppState>, id: u64) -> Result<(), Error> {
+    state.jobs.remove(id)
 }
 
 fn main() {
     tauri::Builder::default()
+        .manage(AppState::default())
         .invoke_handler(tauri::generate_handler![
             load_dataset,
+            cancel_load,
+            save_snapshot,
+            load_snapshot,
+            generate_graph,
+            unload_dataset,
+            list_datasets,
+            select_dataset,
+            get_load_report,
+            get_graph_mode,
+            get_node_labels,
+            find_node,
             get_avg_dg,
             get_max_dg,
             get_cl_ef,
             get_avg_cl_coef,
+            get_transitivity,
             get_avg_cm_nb,
             get_max_cm_ng,
+            get_max_cm_pair,
+            estimate_metric,
             get_dg_dis,
             get_cl_ef_dis,
             get_all_cl_coef,
@@ -249,6 +984,28 @@ fn main() {
             simulate_influnce_spread,
             get_best_starting_nodes,
             louvain_community_detection,
+            export_graph,
+            load_node_attributes,
+            list_node_attributes,
+            get_node_attributes,
+            filter_nodes,
+            group_nodes,
+            get_dg_dis_by_attribute,
+            colour_by_attribute,
+            select_time_window,
+            sample_dataset,
+            edit_graph,
+            add_node,
+            remove_node,
+            add_edge,
+            remove_edge,
+            get_metric_series,
+            submit_job,
+            get_job,
+            list_jobs,
+            get_job_result,
+            cancel_job,
+            remove_job,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -132,7 +132,7 @@ function App() {
 
     const openFilePicker = async () => {
         try {
-            const selected = await open({ directory: false, multiple: false, filters: [{name: 'tsv', extensions: ['tsv']}] });
+            const selected = await open({ directory: false, multiple: false, filters: [{name: 'Graph', extensions: ['tsv', 'txt', 'csv', 'graphml', 'gml', 'net', 'gz', 'bz2', 'zst']}] });
             if (selected) {
                 console.log(selected);
                 load_dataset(selected instanceof Array ? selected[0] : selected);